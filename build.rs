@@ -0,0 +1,35 @@
+//! Lists the per-language data files for `include!`, so adding a language
+//! only takes adding its `locales/<lang>.ftl` and `profiles/<lang>.json`.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    write_table("locales", "ftl", &Path::new(&out_dir).join("catalogs.rs"));
+    write_table("profiles", "json", &Path::new(&out_dir).join("profiles.rs"));
+}
+
+/// Writes `&[(lang, include_str!(path)), ...]` for the files in `dir` with
+/// `extension`, sorted by language.
+fn write_table(dir: &str, extension: &str, dest: &Path) {
+    println!("cargo:rerun-if-changed={}", dir);
+    let root = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join(dir);
+    let mut files = fs::read_dir(&root)
+        .unwrap_or_else(|err| panic!("cannot read {}: {}", root.display(), err))
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != extension {
+                return None;
+            }
+            let lang = path.file_stem()?.to_str()?.to_string();
+            Some((lang, path))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    let mut table = String::from("&[\n");
+    for (lang, path) in files {
+        table.push_str(&format!("    ({:?}, include_str!({:?})),\n", lang, path.display().to_string()));
+    }
+    table.push(']');
+    fs::write(dest, table).unwrap();
+}
@@ -0,0 +1,129 @@
+# English
+# This catalog is the fallback: every message used by the app should be
+# defined here, other catalogs may leave messages out.
+lang-name = English
+
+nav-lang-link = Language
+nav-blogs = Blogs
+nav-projects = Projects
+nav-learning = Learning
+nav-about = About
+
+constructing = Constructing...
+not-found = Page not found
+back-home = Back to home
+
+last-update = Last update
+
+## Relative time, selected by CLDR plural category of $count
+time-now = just now
+time-past-minute = { $count ->
+    [one] { $count } minute ago
+   *[other] { $count } minutes ago
+}
+time-future-minute = { $count ->
+    [one] in { $count } minute
+   *[other] in { $count } minutes
+}
+time-past-hour = { $count ->
+    [one] { $count } hour ago
+   *[other] { $count } hours ago
+}
+time-future-hour = { $count ->
+    [one] in { $count } hour
+   *[other] in { $count } hours
+}
+time-past-day = { $count ->
+    [one] { $count } day ago
+   *[other] { $count } days ago
+}
+time-future-day = { $count ->
+    [one] in { $count } day
+   *[other] in { $count } days
+}
+time-past-week = { $count ->
+    [one] { $count } week ago
+   *[other] { $count } weeks ago
+}
+time-future-week = { $count ->
+    [one] in { $count } week
+   *[other] in { $count } weeks
+}
+time-past-month = { $count ->
+    [one] { $count } month ago
+   *[other] { $count } months ago
+}
+time-future-month = { $count ->
+    [one] in { $count } month
+   *[other] in { $count } months
+}
+time-past-year = { $count ->
+    [one] { $count } year ago
+   *[other] { $count } years ago
+}
+time-future-year = { $count ->
+    [one] in { $count } year
+   *[other] in { $count } years
+}
+
+cached-notice = Offline: showing cached data from { $when }
+retry = Retry
+fetch-error-network = Network error: { $detail }
+fetch-error-timeout = The request timed out
+fetch-error-status = { $status ->
+    [404] Feed not found (404)
+    [403] Access to the feed was denied (403)
+   *[other] The server responded with status { $status }
+}
+fetch-error-content-type = Not a feed (served as { $type })
+fetch-error-parse = Not a readable feed: { $detail }
+parse-warnings = Some entries could not be parsed
+empty-title = Untitled
+no-source = No blog source configured for this language
+previous-page = Previous
+next-page = Next
+load-more = Load more
+sort-newest = Newest
+sort-oldest = Oldest
+sort-title = Title
+filter-all-tags = All tags
+filter-from = From
+filter-to = To
+
+search-placeholder = Search posts
+search-loading = Loading...
+search-no-results = No matching posts
+search-failed = Posts could not be loaded: { $reason }
+
+project-sort-pushed = Recently pushed
+project-sort-stars = Most stars
+project-sort-name = Name
+project-pinned = Pinned
+project-pushed = Pushed { $when }
+project-snapshot = GitHub is unreachable, showing a saved list ({ $reason }).
+project-empty = No public repositories
+
+reader-minutes = { $minutes } min read
+reader-toc = Contents
+reader-original = Open original
+reader-fallback = This post cannot be shown here.
+reader-invalid = This address cannot be opened in the reader
+
+about-more = More about me
+about-skills = Skills
+about-timeline = Timeline
+
+no-learning-source = No learning feed configured for this language
+learning-empty = Nothing here yet
+learning-note = Note
+learning-reading = Reading
+learning-other = Other
+learning-progress = { $percent }% done
+learning-count = { $count ->
+    [one] { $count } entry
+   *[other] { $count } entries
+}
+
+theme-light = Light
+theme-dark = Dark
+theme-system = System theme
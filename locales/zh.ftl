@@ -0,0 +1,89 @@
+# 简体中文
+# Each catalog must define `lang-name`, shown in the language menu.
+lang-name = 中文
+
+nav-lang-link = 语言
+nav-blogs = 文章
+nav-projects = 项目
+nav-learning = 学习
+nav-about = 关于
+
+constructing = 建设中...
+not-found = 页面不存在
+back-home = 返回首页
+
+last-update = 最后更新
+
+## Relative time, selected by CLDR plural category of $count
+time-now = 刚刚
+time-past-minute = { $count } 分钟前
+time-future-minute = { $count } 分钟后
+time-past-hour = { $count } 小时前
+time-future-hour = { $count } 小时后
+time-past-day = { $count } 天前
+time-future-day = { $count } 天后
+time-past-week = { $count } 周前
+time-future-week = { $count } 周后
+time-past-month = { $count } 个月前
+time-future-month = { $count } 个月后
+time-past-year = { $count } 年前
+time-future-year = { $count } 年后
+
+cached-notice = 离线：显示的是{ $when }缓存的数据
+retry = 重试
+fetch-error-network = 网络错误：{ $detail }
+fetch-error-timeout = 请求超时
+fetch-error-status = { $status ->
+    [404] 找不到订阅源 (404)
+    [403] 无权访问订阅源 (403)
+   *[other] 服务器返回状态码 { $status }
+}
+fetch-error-content-type = 不是订阅源（类型为 { $type }）
+fetch-error-parse = 无法解析订阅源：{ $detail }
+parse-warnings = 部分条目无法解析
+empty-title = 无标题
+no-source = 没有为该语言配置博客源
+previous-page = 上一页
+next-page = 下一页
+load-more = 加载更多
+sort-newest = 最新
+sort-oldest = 最早
+sort-title = 标题
+filter-all-tags = 全部标签
+filter-from = 起始日期
+filter-to = 截止日期
+
+search-placeholder = 搜索文章
+search-loading = 正在加载……
+search-no-results = 没有找到相关文章
+search-failed = 无法加载文章：{ $reason }
+
+project-sort-pushed = 最近推送
+project-sort-stars = 星标最多
+project-sort-name = 名称
+project-pinned = 置顶
+project-pushed = { $when }推送
+project-snapshot = 无法访问 GitHub，显示的是保存的列表（{ $reason }）。
+project-empty = 没有公开的仓库
+
+reader-minutes = 阅读约 { $minutes } 分钟
+reader-toc = 目录
+reader-original = 查看原文
+reader-fallback = 无法在站内显示这篇文章。
+reader-invalid = 阅读器无法打开这个地址
+
+about-more = 更多关于我
+about-skills = 技能
+about-timeline = 经历
+
+no-learning-source = 没有为该语言配置学习源
+learning-empty = 暂无内容
+learning-note = 笔记
+learning-reading = 阅读
+learning-other = 其他
+learning-progress = 已完成 { $percent }%
+learning-count = { $count } 项
+
+theme-light = 浅色
+theme-dark = 深色
+theme-system = 跟随系统
@@ -0,0 +1,224 @@
+use serde::Deserialize;
+use yew::{prelude::*, Properties};
+
+use crate::lang::Language;
+use crate::route::AppRoute;
+
+/// The owner's profile per language, one per `profiles/<lang>.json` and
+/// sorted by language; `build.rs` lists them. Languages without one show
+/// the `lang::FALLBACK` profile.
+const PROFILES: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/profiles.rs"));
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// One line under the name.
+    #[serde(default)]
+    pub tagline: String,
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// Paragraphs; the first one is shown on the home page.
+    #[serde(default)]
+    pub bio: Vec<String>,
+    #[serde(default)]
+    pub links: Vec<ProfileLink>,
+    #[serde(default)]
+    pub skills: Vec<SkillGroup>,
+    /// Milestones, newest first.
+    #[serde(default)]
+    pub timeline: Vec<Milestone>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileLink {
+    pub label: String,
+    pub url: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SkillGroup {
+    pub name: String,
+    pub items: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Milestone {
+    /// As it should read, e.g. `2021` or `2021-03`.
+    pub date: String,
+    pub title: String,
+    #[serde(default)]
+    pub detail: String,
+}
+
+impl Profile {
+    pub fn parse(data: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(data)
+    }
+}
+
+/// The profile for `lang`, or `None` if its file is broken.
+pub fn profile(lang: &str) -> Option<Profile> {
+    let (lang, data) = PROFILES
+        .iter()
+        .find(|(l, _)| *l == lang)
+        .or_else(|| PROFILES.iter().find(|(l, _)| *l == crate::lang::FALLBACK))?;
+    match Profile::parse(data) {
+        Ok(profile) => Some(profile),
+        Err(err) => {
+            yew::services::ConsoleService::error(&format!("invalid profile {}: {}", lang, err));
+            None
+        }
+    }
+}
+
+/// Who runs the site. `compact` is the home page card: avatar, name, the
+/// first paragraph and links. Otherwise the whole profile with skills and
+/// timeline, for the About page.
+pub struct AboutTile {
+    language: Language,
+    profile: Option<Profile>,
+    props: AboutProperty,
+}
+
+#[derive(Properties, Clone)]
+pub struct AboutProperty {
+    #[prop_or("zh")]
+    pub lang: &'static str,
+    #[prop_or_default]
+    pub compact: bool,
+}
+
+impl Component for AboutTile {
+    type Message = ();
+
+    type Properties = AboutProperty;
+
+    fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
+        Self {
+            language: Language::from_lang(props.lang),
+            profile: profile(props.lang),
+            props,
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = self.props.lang != props.lang || self.props.compact != props.compact;
+        if self.props.lang != props.lang {
+            self.language = Language::from_lang(props.lang);
+            self.profile = profile(props.lang);
+        }
+        self.props = props;
+        changed
+    }
+
+    fn view(&self) -> Html {
+        let profile = match &self.profile {
+            Some(profile) => profile,
+            None => return html! { <p style="padding:10px;">{self.language.text("constructing")}</p> },
+        };
+        let bio = if self.props.compact { &profile.bio[..profile.bio.len().min(1)] } else { &profile.bio[..] };
+        html! {
+            <div style="padding:10px;">
+                <article class="media">
+                    { for profile.avatar.iter().map(|src| html! {
+                        <figure class="media-left">
+                            <p class="image is-96x96">
+                                <img class="is-rounded" src=src.as_str() alt=profile.name.as_str() />
+                            </p>
+                        </figure>
+                    }) }
+                    <div class="media-content">
+                        <p class="title is-4">{&profile.name}</p>
+                        { if profile.tagline.is_empty() { html! {} } else { html! { <p class="subtitle is-6">{&profile.tagline}</p> } } }
+                        <div class="content">
+                            { for bio.iter().map(|paragraph| html! { <p>{paragraph}</p> }) }
+                        </div>
+                        { self.render_links(profile) }
+                    </div>
+                </article>
+                { if self.props.compact { html! {} } else { self.render_skills(profile) } }
+                { if self.props.compact { html! {} } else { self.render_timeline(profile) } }
+            </div>
+        }
+    }
+}
+
+impl AboutTile {
+    fn render_links(&self, profile: &Profile) -> Html {
+        let more = if self.props.compact {
+            html! {
+                <a class="button is-small is-primary is-outlined" href=AppRoute::About.to_hash()>
+                    {self.language.text("about-more")}
+                </a>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <div class="buttons">
+                { for profile.links.iter().map(|link| html! {
+                    <a class="button is-small" href=link.url.as_str() target="_blank" rel="noopener">{&link.label}</a>
+                }) }
+                { more }
+            </div>
+        }
+    }
+
+    fn render_skills(&self, profile: &Profile) -> Html {
+        if profile.skills.is_empty() {
+            return html! {};
+        }
+        html! {
+            <>
+                <p class="title is-5" style="margin-top:1.5rem;">{self.language.text("about-skills")}</p>
+                { for profile.skills.iter().map(|group| html! {
+                    <div class="field is-grouped is-grouped-multiline">
+                        <span class="control"><strong>{&group.name}</strong></span>
+                        <div class="control">
+                            <div class="tags">
+                            { for group.items.iter().map(|item| html! { <span class="tag is-info is-light">{item}</span> }) }
+                            </div>
+                        </div>
+                    </div>
+                }) }
+            </>
+        }
+    }
+
+    fn render_timeline(&self, profile: &Profile) -> Html {
+        if profile.timeline.is_empty() {
+            return html! {};
+        }
+        html! {
+            <>
+                <p class="title is-5" style="margin-top:1.5rem;">{self.language.text("about-timeline")}</p>
+                { for profile.timeline.iter().map(|milestone| html! {
+                    <div class="columns is-mobile">
+                        <div class="column is-narrow has-text-grey" style="min-width:5rem;">{&milestone.date}</div>
+                        <div class="column">
+                            <p><strong>{&milestone.title}</strong></p>
+                            { if milestone.detail.is_empty() { html! {} } else { html! { <p>{&milestone.detail}</p> } } }
+                        </div>
+                    </div>
+                }) }
+            </>
+        }
+    }
+}
+
+#[test]
+fn test_profiles() {
+    for (lang, data) in PROFILES {
+        let profile = Profile::parse(data).unwrap_or_else(|err| panic!("{}: {}", lang, err));
+        assert!(!profile.name.is_empty(), "{}", lang);
+        assert!(crate::lang::languages().any(|l| l == *lang), "{}", lang);
+    }
+    assert_eq!(profile("fr"), profile(crate::lang::FALLBACK));
+    assert!(profile(crate::lang::FALLBACK).is_some());
+    let minimal = Profile::parse(r#"{"name": "Someone"}"#).unwrap();
+    assert!(minimal.bio.is_empty() && minimal.links.is_empty() && minimal.timeline.is_empty());
+}
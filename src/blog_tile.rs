@@ -1,29 +1,44 @@
-use chrono::{Duration, prelude::*};
-use fetch::FetchTask;
-use wasm_bindgen::prelude::*;
-use xml::reader::{EventReader, XmlEvent};
-use ybc::NavbarFixed::Top;
-use ybc::NavbarItemTag::{Div, A};
-use ybc::TileCtx::{Ancestor, Child, Parent};
-use ybc::TileSize::Four;
+use chrono::prelude::*;
+use feed::ParseWarning;
+use blog_card::{BlogCard, CardLayout};
+pub use request::{fetch_feed, FeedTask, FetchError, ResponseMeta};
+use slots::{FeedSlots, FeedStatus};
+use store::FeedState;
+use serde::{Deserialize, Serialize};
+use filter::{BlogFilter, SortOrder};
+use std::time::Duration;
+
 use yew::{
-    format::Nothing,
     prelude::*,
-    services::fetch::Response,
-    services::fetch::{self, FetchOptions},
+    services::interval::{IntervalService, IntervalTask},
+    ChangeData,
 };
-use yew::{services::ConsoleService, Properties};
-use yewtil::future::LinkFuture;
+use yew::Properties;
 
+use crate::config::FeedSource;
 use crate::lang::Language;
+use crate::route::{self, RouteListener};
+use crate::util::WindowListener;
 
 pub mod blog_card;
-
-#[derive(Debug)]
-struct Blog {
-    title: String,
-    link: String,
-    last_update: DateTime<Utc>,
+pub mod cache;
+pub mod feed;
+pub mod filter;
+pub mod request;
+pub mod slots;
+pub mod store;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blog {
+    pub title: String,
+    pub link: String,
+    pub last_update: DateTime<Utc>,
+    pub summary: String,
+    pub tags: Vec<String>,
+    pub author: String,
+    pub cover_image: Option<String>,
+    /// Index of the configured source this entry was fetched from.
+    pub source: usize,
 }
 
 impl Default for Blog {
@@ -31,40 +46,109 @@ impl Default for Blog {
         Self {
             title: String::new(),
             link: String::new(),
-            last_update: Utc.timestamp_millis(0),
+            last_update: crate::util::epoch(),
+            summary: String::new(),
+            tags: Vec::new(),
+            author: String::new(),
+            cover_image: None,
+            source: 0,
         }
     }
 }
 
-#[derive(Debug)]
+/// Test fixture: an entry with a link made from its title, published
+/// `millis` after the epoch.
+#[cfg(test)]
+pub(crate) fn blog(title: &str, tags: &[&str], millis: i64) -> Blog {
+    Blog {
+        title: title.to_string(),
+        link: format!("https://example.com/posts/{}/", title),
+        last_update: crate::util::from_millis(millis),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Blog::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Blogs {
-    last_update: DateTime<Utc>,
-    blogs: Vec<Blog>,
+    pub last_update: DateTime<Utc>,
+    pub blogs: Vec<Blog>,
+    pub warnings: Vec<ParseWarning>,
+    /// How the feed was served; `None` when several feeds were merged.
+    #[serde(default)]
+    pub meta: Option<ResponseMeta>,
 }
 
 pub struct BlogTile {
     link: ComponentLink<Self>,
     language: Language,
-    blogs: BlogStatus,
-    fetch_task: Option<FetchTask>,
+    /// One slot per entry of `sources()`.
+    feeds: FeedSlots,
+    /// Zero-based page, or the number of extra pages loaded in `load_more` mode.
+    page: usize,
+    filter: BlogFilter,
+    route_listener: Option<RouteListener>,
+    scroll_listener: Option<WindowListener>,
+    _tick_task: IntervalTask,
+    poll_task: Option<IntervalTask>,
     props: BlogProperty,
 }
 
+/// How often relative times such as "5 minutes ago" are re-rendered.
+const TICK: Duration = Duration::from_secs(30);
+
 #[derive(Properties, Clone)]
 pub struct BlogProperty {
     #[prop_or("zh")]
     pub lang: &'static str,
+    /// Index into the blog sources configured for `lang`.
+    #[prop_or_default]
+    pub source: usize,
+    /// Fetch every source configured for `lang` and show one merged timeline
+    /// instead of the single `source`.
+    #[prop_or_default]
+    pub merged: bool,
+    /// Cards per page; `0` shows every entry at once.
+    #[prop_or(6)]
+    pub page_size: usize,
+    /// Append the next page when the reader scrolls to the end or presses
+    /// "load more", instead of showing previous/next controls.
+    #[prop_or_default]
+    pub load_more: bool,
+    /// Initial order of the entries.
+    #[prop_or_default]
+    pub sort: SortOrder,
+    /// Show sort, tag and date range controls above the cards.
+    #[prop_or_default]
+    pub controls: bool,
+    /// Keep the page, sort order and filters in the hash query
+    /// (`#/blogs?page=2&sort=oldest&tag=Rust`) so they survive reloads and
+    /// follow back/forward. Only one tile per page should set this.
+    #[prop_or_default]
+    pub url_state: bool,
+    /// Re-fetch the feeds in the background every this many seconds; `0`
+    /// fetches once. Polls revalidate against the browser cache, so an
+    /// unchanged feed costs a `304` instead of a full download.
+    #[prop_or_default]
+    pub poll_secs: u64,
+    /// Lead with the first entry as a large featured card.
+    #[prop_or_default]
+    pub featured: bool,
+    /// Color of the featured card, e.g. `Theme::hero()`.
+    #[prop_or("is-light")]
+    pub hero_classes: &'static str,
 }
 
 pub enum BlogMessage {
-    Done(Blogs),
-    Error(String),
-}
-
-enum BlogStatus {
-    Fetching,
-    Done(Blogs),
-    Err(String),
+    Feed(FeedState),
+    Page(usize),
+    Filter(BlogFilter),
+    LoadMore,
+    Scrolled,
+    LocationChanged,
+    Retry,
+    Tick,
+    Poll,
 }
 
 impl Component for BlogTile {
@@ -73,66 +157,129 @@ impl Component for BlogTile {
     type Properties = BlogProperty;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let task = fetch_sitemap(&link, props.lang);
-
-        Self {
-            link,
+        let mut tile = Self {
+            link: link.clone(),
             language: Language::from_lang(props.lang),
-            blogs: BlogStatus::Fetching,
-            fetch_task: Some(task),
+            feeds: FeedSlots::new(link.callback(BlogMessage::Feed)),
+            page: 0,
+            filter: BlogFilter::default(),
+            route_listener: None,
+            scroll_listener: None,
+            _tick_task: IntervalService::spawn(TICK, link.callback(|_| BlogMessage::Tick)),
+            poll_task: None,
             props,
-        }
+        };
+        tile.listen();
+        tile.read_url();
+        tile.schedule_poll();
+        tile.fetch();
+        tile
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            BlogMessage::Done(blogs) => {
-                match &self.blogs {
-                    BlogStatus::Done(b) => {
-                        if b.last_update == blogs.last_update {
-                            return false;
-                        }
-                    }
-                    _ => {}
+            BlogMessage::Feed(state) => self.feeds.update(state, &self.language),
+            BlogMessage::Page(page) => {
+                self.page = page;
+                self.write_url();
+                true
+            }
+            BlogMessage::Filter(filter) => {
+                self.filter = filter;
+                self.page = 0;
+                self.write_url();
+                true
+            }
+            BlogMessage::LoadMore | BlogMessage::Scrolled => {
+                let scrolled = matches!(msg, BlogMessage::Scrolled);
+                if !self.has_more() || (scrolled && !crate::util::near_page_bottom(200.0)) {
+                    return false;
                 }
-                self.blogs = BlogStatus::Done(blogs);
+                self.page += 1;
+                self.write_url();
+                true
+            }
+            BlogMessage::LocationChanged => {
+                let (page, filter) = (self.page, self.filter.clone());
+                self.read_url();
+                page != self.page || filter != self.filter
+            }
+            BlogMessage::Retry => {
+                self.feeds.retry(&self.language);
+                true
             }
-            BlogMessage::Error(err) => {
-                self.blogs = BlogStatus::Err(err);
+            BlogMessage::Tick => matches!(self.feeds.status(), FeedStatus::Done(_)),
+            BlogMessage::Poll => {
+                self.feeds.poll();
+                false
             }
         }
-        true
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        if self.props.lang == _props.lang {
-            false
+        if self.props.lang == _props.lang
+            && self.props.source == _props.source
+            && self.props.merged == _props.merged
+        {
+            let view_changed = self.props.page_size != _props.page_size
+                || self.props.load_more != _props.load_more
+                || self.props.sort != _props.sort
+                || self.props.controls != _props.controls
+                || self.props.url_state != _props.url_state
+                || self.props.featured != _props.featured
+                || self.props.hero_classes != _props.hero_classes;
+            let poll_changed = self.props.poll_secs != _props.poll_secs;
+            self.props = _props;
+            if poll_changed {
+                self.schedule_poll();
+            }
+            if view_changed {
+                self.listen();
+                self.read_url();
+            }
+            view_changed
         } else {
             self.props = _props;
             self.language = Language::from_lang(self.props.lang);
-            self.fetch_task = Some(fetch_sitemap(&self.link, self.props.lang));
+            // Another list of feeds: the old page may be past its end.
+            self.page = 0;
+            self.write_url();
+            self.listen();
+            self.schedule_poll();
+            self.fetch();
             true
         }
     }
 
     fn view(&self) -> Html {
-        let status = match &self.blogs {
-            BlogStatus::Fetching => {
+        let sources = self.sources();
+        let single = match sources.as_slice() {
+            [(_, source)] if !self.props.merged => Some(*source),
+            _ => None,
+        };
+        let title = single.map_or_else(|| self.language.text("nav-blogs"), |s| s.name.clone());
+        let badge = match single.and_then(|s| s.badge.as_ref()) {
+            Some(badge) => html! { <img src=badge.as_str() /> },
+            None => html! {},
+        };
+        let status = match self.feeds.status() {
+            FeedStatus::Fetching => {
                 html! {
                     <>
                     <progress class="progress is-small is-primary" max="100"> {"12312"} </progress>
                     </>
                 }
             }
-            BlogStatus::Done(blogs) => {
+            FeedStatus::Done(blogs) => {
                 let duration = crate::util::now() - blogs.last_update;
                 html! {
                     <>
-                        {self.language.last_update}{":"}{display_duration(duration, &self.language)}
+                        {self.language.text("last-update")}{":"}{self.language.relative_time(duration)}
+                        { self.feeds.render_cached_notice(&self.language) }
                     </>
                 }
             }
-            BlogStatus::Err(err) => {
+            FeedStatus::Err(err) => {
                 html! {
                     <>
                         <p>{err}</p>
@@ -140,27 +287,45 @@ impl Component for BlogTile {
                 }
             }
         };
-        let content = match &self.blogs {
-            BlogStatus::Fetching => {
+        let content = match self.feeds.status() {
+            FeedStatus::Fetching => {
                 html! {
                     <>
                     </>
                 }
             }
-            BlogStatus::Done(blogs) => {
-                let duration = crate::util::now() - blogs.last_update;
+            FeedStatus::Done(blogs) => {
+                let (featured, shown) = self.shown(blogs);
+                let featured = match featured {
+                    Some(blog) if self.page == 0 || self.props.load_more => html! {
+                        <BlogCard lang={self.language.lang} blog=blog.clone() layout=CardLayout::Featured
+                            hero_classes=self.props.hero_classes
+                            source=self.source_badge(blog).map(str::to_string) />
+                    },
+                    _ => html! {},
+                };
+                let range = page_range(shown.len(), self.props.page_size, self.page, self.props.load_more);
                 html! {
                     <>
-                        <div class="columns is-gapless">
-                        { blogs.blogs.iter().map(render_blog).collect::<Html>() }
+                        { self.render_controls(blogs) }
+                        { featured }
+                        <div class="columns is-multiline is-gapless">
+                        { for shown[range].iter().map(|blog| html! {
+                            <BlogCard lang={self.language.lang} blog=(*blog).clone() source=self.source_badge(blog).map(str::to_string) />
+                        }) }
                         </div>
+                        { self.render_paging(shown.len()) }
+                        { self.render_source_errors() }
+                        { render_warnings(&blogs.warnings, &self.language) }
                     </>
                 }
             }
-            BlogStatus::Err(err) => {
+            FeedStatus::Err(err) => {
+                let failed = !self.feeds.source_errors(&self.language).is_empty();
                 html! {
                     <>
                         <p>{err}</p>
+                        { if failed { self.render_retry() } else { html! {} } }
                     </>
                 }
             }
@@ -172,10 +337,10 @@ impl Component for BlogTile {
                     <figure class="image is-48x48">
                     <img src="https://bulma.io/images/placeholders/96x96.png" alt="Placeholder image" />
                     </figure>
-                    <ybc::Title>{"Blogs"}</ybc::Title>
+                    <ybc::Title>{title}</ybc::Title>
                     </div>
                     <div class="media-content">
-                    <p class="title is-4"><img src="https://github.com/another-s347/md-pages/workflows/Auto-Deploy/badge.svg?event=push" /></p>
+                    <p class="title is-4">{badge}</p>
                     <p class="subtitle is-6">{status}</p>
                   </div>
                 </ybc::Media>
@@ -191,216 +356,392 @@ impl Component for BlogTile {
     fn destroy(&mut self) {}
 }
 
-fn render_blog(blog: &Blog) -> Html {
-    let title = if blog.title.is_empty() { "Empty title" } else { blog.title.as_str() };
-    html! {
-        <div class="column">
-        <ybc::Message>
-            <div class="message-header" style="display:block;">
-                <h4 class="title is-4 is-spaced">
-                    {title}
-                </h4>
-                <ybc::Subtitle classes="is-5">{&blog.last_update}</ybc::Subtitle>
-
-                <span class="tag">
-                {"Tag label"}
-                </span>
-
-                <span class="tag">
-                {"Tag label"}
-                </span>
-            </div>
-            <ybc::MessageBody>
-                {"blablablabla"}
-            </ybc::MessageBody>
-            // <a href={blog.link.as_str()}>{title}</a>
-        </ybc::Message>
-        </div>
+impl BlogTile {
+    fn listen(&mut self) {
+        self.route_listener = if self.props.url_state {
+            Some(RouteListener::new(self.link.callback(|_| BlogMessage::LocationChanged)))
+        } else {
+            None
+        };
+        self.scroll_listener = if self.props.load_more {
+            Some(WindowListener::new("scroll", self.link.callback(|_| BlogMessage::Scrolled)))
+        } else {
+            None
+        };
     }
-}
 
-fn display_duration(duration:Duration, lang: &Language) -> String {
-    if duration.num_seconds() < 0 {
-        panic!()
+    /// Pages are one-based in the URL.
+    fn read_url(&mut self) {
+        if !self.props.url_state {
+            self.filter = BlogFilter {
+                sort: self.props.sort,
+                ..self.filter.clone()
+            };
+            return;
+        }
+        self.page = route::query_param("page")
+            .and_then(|page| page.parse::<usize>().ok())
+            .and_then(|page| page.checked_sub(1))
+            .unwrap_or_default();
+        self.filter = BlogFilter::from_query(self.props.sort, route::query_param);
     }
-    if duration.num_weeks() > 0 {
-        return format!("{} {} {}", duration.num_weeks(), lang.time_week, lang.time_ago)
+
+    fn write_url(&self) {
+        if !self.props.url_state {
+            return;
+        }
+        let mut params = self.filter.to_query(self.props.sort);
+        params.push(("page", Some(self.page + 1).filter(|page| *page > 1).map(|page| page.to_string())));
+        route::set_query_params(&params);
     }
-    else if duration.num_days() > 0 {
-        return format!("{} {} {}", duration.num_days(), lang.time_day, lang.time_ago)
+
+    /// The entries that pass the filter, with the first split off when
+    /// the tile leads with a `featured` card.
+    fn shown<'a>(&self, blogs: &'a Blogs) -> (Option<&'a Blog>, Vec<&'a Blog>) {
+        let mut shown = self.filter.apply(&blogs.blogs);
+        if self.props.featured && !shown.is_empty() {
+            let featured = shown.remove(0);
+            (Some(featured), shown)
+        } else {
+            (None, shown)
+        }
     }
-    else if duration.num_hours() > 0 {
-        return format!("{} {} {}", duration.num_hours(), lang.time_hour, lang.time_ago)
+
+    fn has_more(&self) -> bool {
+        match self.feeds.status() {
+            FeedStatus::Done(blogs) => {
+                let len = self.shown(blogs).1.len();
+                page_range(len, self.props.page_size, self.page, self.props.load_more).end < len
+            }
+            _ => false,
+        }
     }
-    else if duration.num_minutes() > 0 {
-        return format!("{} {} {}", duration.num_minutes(), lang.time_minutes, lang.time_ago)
+
+    fn render_controls(&self, blogs: &Blogs) -> Html {
+        if !self.props.controls {
+            return html! {};
+        }
+        let lang = &self.language;
+        let filter = &self.filter;
+        let on_sort = {
+            let filter = filter.clone();
+            self.link.callback(move |change: ChangeData| {
+                let sort = match change {
+                    ChangeData::Select(select) => SortOrder::parse(&select.value()),
+                    _ => None,
+                };
+                BlogMessage::Filter(BlogFilter {
+                    sort: sort.unwrap_or_default(),
+                    ..filter.clone()
+                })
+            })
+        };
+        let on_tag = {
+            let filter = filter.clone();
+            self.link.callback(move |change: ChangeData| {
+                let tag = match change {
+                    ChangeData::Select(select) => Some(select.value()).filter(|tag| !tag.is_empty()),
+                    _ => None,
+                };
+                BlogMessage::Filter(BlogFilter {
+                    tag,
+                    ..filter.clone()
+                })
+            })
+        };
+        let on_date = |to: bool| {
+            let filter = filter.clone();
+            self.link.callback(move |change: ChangeData| {
+                let date = match change {
+                    ChangeData::Value(value) => chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok(),
+                    _ => None,
+                };
+                let mut filter = filter.clone();
+                if to {
+                    filter.to = date;
+                } else {
+                    filter.from = date;
+                }
+                BlogMessage::Filter(filter)
+            })
+        };
+        let sort_label = |order: SortOrder| match order {
+            SortOrder::Newest => lang.text("sort-newest"),
+            SortOrder::Oldest => lang.text("sort-oldest"),
+            SortOrder::Title => lang.text("sort-title"),
+        };
+        let date_value = |date: Option<chrono::NaiveDate>| {
+            date.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default()
+        };
+        html! {
+            <div class="field is-grouped is-grouped-multiline" style="padding:0px 10px;">
+                <div class="control">
+                    <div class="select is-small">
+                        <select onchange=on_sort>
+                        { for SortOrder::ALL.iter().map(|order| html! {
+                            <option value=order.as_str() selected={*order == filter.sort}>{sort_label(*order)}</option>
+                        }) }
+                        </select>
+                    </div>
+                </div>
+                <div class="control">
+                    <div class="select is-small">
+                        <select onchange=on_tag>
+                            <option value="" selected={filter.tag.is_none()}>{lang.text("filter-all-tags")}</option>
+                            { for filter::all_tags(&blogs.blogs).into_iter().map(|tag| html! {
+                                <option value=tag selected={filter.tag.as_deref() == Some(tag)}>{tag}</option>
+                            }) }
+                        </select>
+                    </div>
+                </div>
+                <div class="control">
+                    <input class="input is-small" type="date" title=lang.text("filter-from")
+                        value=date_value(filter.from) onchange=on_date(false) />
+                </div>
+                <div class="control">
+                    <input class="input is-small" type="date" title=lang.text("filter-to")
+                        value=date_value(filter.to) onchange=on_date(true) />
+                </div>
+            </div>
+        }
     }
-    else {
-        return lang.time_just.to_string()
+
+    fn render_paging(&self, len: usize) -> Html {
+        if self.props.load_more {
+            return if self.has_more() {
+                html! {
+                    <div class="has-text-centered" style="padding:10px;">
+                        <button class="button is-small" onclick=self.link.callback(|_| BlogMessage::LoadMore)>
+                            {self.language.text("load-more")}
+                        </button>
+                    </div>
+                }
+            } else {
+                html! {}
+            };
+        }
+        let count = page_count(len, self.props.page_size);
+        if count <= 1 {
+            return html! {};
+        }
+        let page = self.page.min(count - 1);
+        html! {
+            <nav class="pagination is-centered is-small" role="navigation" style="padding:10px;">
+                <button class="pagination-previous" disabled={page == 0}
+                    onclick=self.link.callback(move |_| BlogMessage::Page(page.saturating_sub(1)))>
+                    {self.language.text("previous-page")}
+                </button>
+                <button class="pagination-next" disabled={page + 1 >= count}
+                    onclick=self.link.callback(move |_| BlogMessage::Page(page + 1))>
+                    {self.language.text("next-page")}
+                </button>
+                <ul class="pagination-list">
+                    <li><span class="pagination-ellipsis">{page + 1}{" / "}{count}</span></li>
+                </ul>
+            </nav>
+        }
     }
-}
 
-fn fetch_sitemap(link: &ComponentLink<BlogTile>, lang: &'static str) -> FetchTask {
-    ConsoleService::log("fetch");
-    ConsoleService::log(lang);
-    let url = match lang {
-        "en" => "https://another-s347.github.io/blogs-en/custom_sitemap.xml",
-        "zh" => "http://another-s347.github.io/blogs/custom_sitemap.xml",
-        _ => panic!("unknown lang"),
-    };
-    let callback = link.callback(
-        |response: Response<Result<String, anyhow::Error>>| -> BlogMessage {
-            match response.body() {
-                Ok(s) => match parse_sitemap_to_blog(s.as_str()) {
-                    Ok(s) => BlogMessage::Done(s),
-                    Err(err) => BlogMessage::Error(err.to_string()),
-                },
-                Err(err) => BlogMessage::Error(err.to_string()),
-            }
-        },
-    );
-    let options = FetchOptions {
-        // mode: Some(yew::web_sys::RequestMode::NoCors),
-        ..FetchOptions::default()
-    };
-    let request = fetch::Request::get(url)
-        .body(Nothing)
-        .expect("build request failed");
-    yew::services::FetchService::fetch_with_options(request, options, callback)
-        .expect("fetch failed")
-}
+    /// Configured sources this tile shows, with their index in `sources.json`.
+    fn sources(&self) -> Vec<(usize, &'static FeedSource)> {
+        let sources = crate::config::site().blog_sources(self.props.lang);
+        if self.props.merged {
+            sources.iter().enumerate().collect()
+        } else {
+            sources.get(self.props.source).map(|s| (self.props.source, s)).into_iter().collect()
+        }
+    }
+
+    fn fetch(&mut self) {
+        self.feeds.watch(self.sources(), self.language.text("no-source"));
+    }
+
+    fn schedule_poll(&mut self) {
+        self.poll_task = match self.props.poll_secs {
+            0 => None,
+            secs => Some(IntervalService::spawn(
+                Duration::from_secs(secs),
+                self.link.callback(|_| BlogMessage::Poll),
+            )),
+        };
+    }
 
-enum Parser {
-    Enter(Blog, SetState),
-    Exit,
+    fn source_badge(&self, blog: &Blog) -> Option<&'static str> {
+        if !self.props.merged {
+            return None;
+        }
+        crate::config::site()
+            .blog_sources(self.props.lang)
+            .get(blog.source)
+            .map(|s| s.name.as_str())
+    }
+
+    fn render_retry(&self) -> Html {
+        render_retry(&self.language, self.link.callback(|_| BlogMessage::Retry))
+    }
+
+    fn render_source_errors(&self) -> Html {
+        let errors = self.feeds.source_errors(&self.language);
+        if errors.is_empty() {
+            return html! {};
+        }
+        html! {
+            <article class="message is-danger is-small">
+                <div class="message-body">
+                    <ul>
+                    { errors.into_iter().map(|e| html! { <li>{e}</li> }).collect::<Html>() }
+                    </ul>
+                    { self.render_retry() }
+                </div>
+            </article>
+        }
+    }
 }
 
-enum SetState {
-    Loc,
-    LastUpdate,
-    Title,
-    None,
+fn page_count(len: usize, page_size: usize) -> usize {
+    if page_size == 0 {
+        1
+    } else {
+        len.div_ceil(page_size).max(1)
+    }
 }
 
-impl Parser {
-    pub fn new() -> Self {
-        Parser::Exit
+/// Entries to show: one page, or everything up to and including `page` in
+/// load-more mode. Out-of-range pages clamp to the last one.
+fn page_range(len: usize, page_size: usize, page: usize, load_more: bool) -> std::ops::Range<usize> {
+    if page_size == 0 {
+        return 0..len;
+    }
+    let page = page.min(page_count(len, page_size) - 1);
+    let end = len.min((page + 1) * page_size);
+    if load_more {
+        0..end
+    } else {
+        (page * page_size).min(len)..end
     }
+}
 
-    pub fn set(&mut self, data: String) {
-        match self {
-            Parser::Enter(blog, state) => match std::mem::replace(state, SetState::None) {
-                SetState::Loc => {
-                    blog.link = data;
-                }
-                SetState::LastUpdate => {
-                    blog.last_update = Utc.timestamp_millis(data.parse().unwrap());
-                }
-                SetState::Title => {
-                    blog.title = data;
-                }
-                SetState::None => {
-                    panic!("invalid state")
-                }
-            },
-            Parser::Exit => {
-                panic!("invalid state")
-            }
-        }
+/// Merges the feeds into one timeline, newest first. Entries whose links
+/// only differ in scheme, `www.`, trailing slash or fragment are the same
+/// post; the newest copy wins.
+pub fn merge_blogs(parts: &[(usize, &Blogs)]) -> Blogs {
+    let mut blogs = parts
+        .iter()
+        .flat_map(|(source, blogs)| {
+            blogs.blogs.iter().cloned().map(move |mut blog| {
+                blog.source = *source;
+                blog
+            })
+        })
+        .collect::<Vec<_>>();
+    blogs.sort_by_key(|blog| std::cmp::Reverse(blog.last_update));
+    let mut seen = std::collections::HashSet::new();
+    blogs.retain(|blog| seen.insert(canonical_link(&blog.link)));
+
+    Blogs {
+        last_update: parts
+            .iter()
+            .map(|(_, blogs)| blogs.last_update)
+            .max()
+            .unwrap_or_else(crate::util::epoch),
+        blogs,
+        warnings: parts.iter().flat_map(|(_, blogs)| blogs.warnings.iter().cloned()).collect(),
+        meta: match parts {
+            [(_, only)] => only.meta.clone(),
+            _ => None,
+        },
     }
+}
 
-    pub fn enter(&mut self, state: SetState) {
-        match self {
-            Parser::Enter(blog, s) => {
-                *s = state;
-            }
-            Parser::Exit => {
-                *self = Parser::Enter(Blog::default(), state);
+fn canonical_link(link: &str) -> String {
+    match url::Url::parse(link) {
+        Ok(url) => {
+            let host = url.host_str().unwrap_or_default();
+            let host = host.strip_prefix("www.").unwrap_or(host);
+            let mut key = format!("{}{}", host, url.path().trim_end_matches('/'));
+            if let Some(query) = url.query() {
+                key.push('?');
+                key.push_str(query);
             }
+            key
         }
+        Err(_) => link.trim().trim_end_matches('/').to_string(),
     }
+}
 
-    pub fn exit(&mut self) -> Option<Blog> {
-        match std::mem::replace(self, Parser::Exit) {
-            Parser::Enter(blog, state) => Some(blog),
-            Parser::Exit => None,
-        }
+/// The button that fetches whatever failed again.
+pub fn render_retry(lang: &Language, onclick: Callback<MouseEvent>) -> Html {
+    html! {
+        <button class="button is-small is-danger is-outlined" onclick=onclick>
+            {lang.text("retry")}
+        </button>
     }
 }
 
-fn parse_sitemap_to_blog(data: &str) -> anyhow::Result<Blogs> {
-    let mut parser = Parser::new();
-    let mut ret = vec![];
-    for event in xml::reader::EventReader::new(data.as_bytes()) {
-        match event? {
-            XmlEvent::StartElement { name, .. } => {
-                if name.local_name == "loc" {
-                    parser.enter(SetState::Loc);
-                }
-                if name.local_name == "lastmod" {
-                    parser.enter(SetState::LastUpdate);
-                }
-                if name.local_name == "title" {
-                    parser.enter(SetState::Title);
-                }
-            }
-            XmlEvent::EndElement { name } => {
-                if name.local_name == "url" {
-                    ret.push(parser.exit().ok_or(anyhow::anyhow!(""))?);
-                }
-            }
-            XmlEvent::Characters(data) => {
-                parser.set(data);
-            }
-            _ => {}
-        }
+fn render_warnings(warnings: &[ParseWarning], lang: &Language) -> Html {
+    if warnings.is_empty() {
+        return html! {};
+    }
+    html! {
+        <article class="message is-warning is-small">
+            <div class="message-header">
+                <p>{lang.text("parse-warnings")}{" ("}{warnings.len()}{")"}</p>
+            </div>
+            <div class="message-body">
+                <ul>
+                { warnings.iter().map(|w| html! { <li>{w.to_string()}</li> }).collect::<Html>() }
+                </ul>
+            </div>
+        </article>
     }
+}
 
-    let last_update = ret
-        .iter()
-        .map(|x| x.last_update)
-        .max()
-        .unwrap_or(Utc.timestamp_millis(0));
-
-    Ok(Blogs {
-        last_update,
-        blogs: ret,
-    })
+#[test]
+fn test_merge_blogs() {
+    let at = |link: &str, millis| Blog {
+        link: link.to_string(),
+        ..blog("", &[], millis)
+    };
+    let personal = Blogs {
+        last_update: crate::util::from_millis(300),
+        blogs: vec![
+            at("http://another-s347.github.io/blogs/a/", 100),
+            at("http://another-s347.github.io/blogs/b/", 300),
+        ],
+        warnings: vec![],
+        meta: None,
+    };
+    let team = Blogs {
+        last_update: crate::util::from_millis(200),
+        blogs: vec![
+            at("https://www.another-s347.github.io/blogs/a#top", 200),
+            at("https://team.example.com/c", 150),
+        ],
+        warnings: vec![feed::ParseWarning::UnmatchedEnd],
+        meta: None,
+    };
+    let merged = merge_blogs(&[(0, &personal), (1, &team)]);
+    let links = merged.blogs.iter().map(|b| (b.source, b.link.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        links,
+        vec![
+            (0, "http://another-s347.github.io/blogs/b/"),
+            (1, "https://www.another-s347.github.io/blogs/a#top"),
+            (1, "https://team.example.com/c"),
+        ]
+    );
+    assert_eq!(merged.last_update, crate::util::from_millis(300));
+    assert_eq!(merged.warnings.len(), 1);
 }
 
 #[test]
-fn test_parse_sitemap() {
-    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
-    <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
-      
-      <url>
-        <title>使用PyO3跨越编程语言的异步</title>
-        <loc>http://another-s347.github.io/blogs/2021/01/01/async-pyo3-1/</loc>
-        
-        <lastmod>1609859438225</lastmod>
-        
-      </url>
-      
-      <url>
-        <title></title>
-        <loc>http://another-s347.github.io/blogs/2021/01/05/dummy/</loc>
-        
-        <lastmod>1609859415394</lastmod>
-        
-      </url>
-      
-    
-      <!-- <url>
-        <loc>http://another-s347.github.io/blogs</loc>
-        <lastmod>2021-01-05</lastmod>
-        <changefreq>daily</changefreq>
-        <priority>1.0</priority>
-      </url>
-    
-      
-    
-       -->
-    </urlset>
-    "#;
-    println!("{:#?}", parse_sitemap_to_blog(xml));
+fn test_page_range() {
+    assert_eq!(page_range(10, 4, 0, false), 0..4);
+    assert_eq!(page_range(10, 4, 2, false), 8..10);
+    assert_eq!(page_range(10, 4, 7, false), 8..10);
+    assert_eq!(page_range(10, 4, 1, true), 0..8);
+    assert_eq!(page_range(10, 0, 3, false), 0..10);
+    assert_eq!(page_range(0, 4, 1, false), 0..0);
+    assert_eq!(page_count(10, 4), 3);
+    assert_eq!(page_count(0, 4), 1);
 }
@@ -0,0 +1,225 @@
+use yew::{prelude::*, Properties};
+
+use super::Blog;
+use crate::lang::Language;
+use crate::route::AppRoute;
+use crate::search::render_highlight;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CardLayout {
+    /// Title, a line of summary and tags, e.g. for search results.
+    Compact,
+    /// Cover, title, author, date, tags and summary, for lists of posts.
+    #[default]
+    Full,
+    /// A large banner for the post a page leads with.
+    Featured,
+}
+
+/// One post. The title opens the post in the reader; clicks are also
+/// reported through `onclick`, e.g. to close the dropdown a card sits in.
+pub struct BlogCard {
+    link: ComponentLink<Self>,
+    language: Language,
+    props: BlogCardProperty,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct BlogCardProperty {
+    #[prop_or("zh")]
+    pub lang: &'static str,
+    pub blog: Blog,
+    #[prop_or_default]
+    pub layout: CardLayout,
+    #[prop_or(true)]
+    pub show_tags: bool,
+    #[prop_or(true)]
+    pub show_date: bool,
+    /// Name of the feed the post came from, shown as the first tag.
+    #[prop_or_default]
+    pub source: Option<String>,
+    /// Search query whose terms are marked in the title, summary and tags.
+    #[prop_or_default]
+    pub highlight: String,
+    /// Extra classes for the outermost element.
+    #[prop_or_default]
+    pub classes: String,
+    /// Color of the `Featured` hero, e.g. `Theme::hero()`. A cover is
+    /// darkened behind the text, so with one the hero is always `is-dark`.
+    #[prop_or("is-light")]
+    pub hero_classes: &'static str,
+    #[prop_or_default]
+    pub onclick: Callback<Blog>,
+}
+
+pub enum BlogCardMessage {
+    Clicked,
+}
+
+/// Characters of summary shown by compact cards.
+const COMPACT_SUMMARY: usize = 120;
+
+impl Component for BlogCard {
+    type Message = BlogCardMessage;
+
+    type Properties = BlogCardProperty;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            language: Language::from_lang(props.lang),
+            props,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            BlogCardMessage::Clicked => self.props.onclick.emit(self.props.blog.clone()),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props == props {
+            false
+        } else {
+            if self.props.lang != props.lang {
+                self.language = Language::from_lang(props.lang);
+            }
+            self.props = props;
+            true
+        }
+    }
+
+    fn view(&self) -> Html {
+        match self.props.layout {
+            CardLayout::Compact => self.view_compact(),
+            CardLayout::Full => self.view_full(),
+            CardLayout::Featured => self.view_featured(),
+        }
+    }
+}
+
+impl BlogCard {
+    fn title(&self) -> String {
+        let blog = &self.props.blog;
+        if blog.title.is_empty() { self.language.text("empty-title") } else { blog.title.clone() }
+    }
+
+    fn text(&self, text: &str) -> Html {
+        render_highlight(text, &self.props.highlight)
+    }
+
+    fn view_link(&self, classes: &str, content: Html) -> Html {
+        html! {
+            <a class=classes href=AppRoute::Reader(self.props.blog.link.clone()).to_hash()
+                onclick=self.link.callback(|_| BlogCardMessage::Clicked)>
+                { content }
+            </a>
+        }
+    }
+
+    fn view_tags(&self) -> Html {
+        let blog = &self.props.blog;
+        if !self.props.show_tags || (blog.tags.is_empty() && self.props.source.is_none()) {
+            return html! {};
+        }
+        html! {
+            <div class="tags">
+            { for self.props.source.iter().map(|name| html! { <span class="tag is-info">{name}</span> }) }
+            { for blog.tags.iter().map(|tag| html! { <span class="tag">{self.text(tag)}</span> }) }
+            </div>
+        }
+    }
+
+    fn view_byline(&self) -> Html {
+        let blog = &self.props.blog;
+        let author = if blog.author.is_empty() {
+            html! {}
+        } else {
+            html! { <>{&blog.author}{" · "}</> }
+        };
+        let date = if self.props.show_date {
+            html! { {blog.last_update.format("%Y-%m-%d").to_string()} }
+        } else {
+            html! {}
+        };
+        html! { <>{author}{date}</> }
+    }
+
+    fn view_compact(&self) -> Html {
+        let blog = &self.props.blog;
+        let summary = blog.summary.chars().take(COMPACT_SUMMARY).collect::<String>();
+        let content = html! {
+            <>
+                <p><strong>{self.text(&self.title())}</strong></p>
+                { if self.props.show_date { html! { <p class="is-size-7 has-text-grey">{self.view_byline()}</p> } } else { html! {} } }
+                <p class="is-size-7">{self.text(&summary)}</p>
+                { self.view_tags() }
+            </>
+        };
+        self.view_link(&self.props.classes, content)
+    }
+
+    fn view_full(&self) -> Html {
+        let blog = &self.props.blog;
+        let title = &self.title();
+        let cover = match &blog.cover_image {
+            Some(src) => html! {
+                <figure class="image is-3by1" style="overflow:hidden;">
+                    <img src=src.as_str() alt=title style="object-fit:cover;" />
+                </figure>
+            },
+            None => html! {},
+        };
+        let body = if blog.summary.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <ybc::MessageBody>
+                    {self.text(&blog.summary)}
+                </ybc::MessageBody>
+            }
+        };
+        html! {
+            <div class=("column is-half-tablet is-one-third-desktop", self.props.classes.as_str())>
+            <ybc::Message>
+                { cover }
+                <div class="message-header" style="display:block;">
+                    <h4 class="title is-4 is-spaced">
+                        { self.view_link("", self.text(title)) }
+                    </h4>
+                    <ybc::Subtitle classes="is-5">{self.view_byline()}</ybc::Subtitle>
+                    { self.view_tags() }
+                </div>
+                { body }
+            </ybc::Message>
+            </div>
+        }
+    }
+
+    /// The cover is an `<img>` darkened behind the text rather than a CSS
+    /// `background-image`, so its URL never ends up inside a style.
+    fn view_featured(&self) -> Html {
+        let blog = &self.props.blog;
+        let cover = match &blog.cover_image {
+            Some(src) => html! {
+                <img src=src.as_str() alt=""
+                    style="position:absolute;top:0;left:0;width:100%;height:100%;object-fit:cover;filter:brightness(0.5);" />
+            },
+            None => html! {},
+        };
+        let color = if blog.cover_image.is_some() { "is-dark" } else { self.props.hero_classes };
+        html! {
+            <section class=("hero", color, self.props.classes.as_str()) style="position:relative;overflow:hidden;">
+            { cover }
+            <div class="hero-body" style="position:relative;">
+                <p class="subtitle is-6">{self.view_byline()}</p>
+                <h2 class="title is-2">{ self.view_link("", self.text(&self.title())) }</h2>
+                { if blog.summary.is_empty() { html! {} } else { html! { <p class="subtitle">{self.text(&blog.summary)}</p> } } }
+                { self.view_tags() }
+            </div>
+            </section>
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use yew::format::Json;
+use yew::services::{storage::Area, StorageService};
+
+use super::Blogs;
+
+/// Bump when `Blogs` changes shape; entries under other versions are ignored.
+const KEY_PREFIX: &str = "io-index.feed.v1:";
+/// Bump when a type cached through `store_json` changes shape.
+const JSON_PREFIX: &str = "io-index.json.v1:";
+
+/// A parsed feed as last fetched, kept in `localStorage` so the next visit
+/// can render it before the network answers, or instead of it when offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFeed {
+    pub fetched_at: DateTime<Utc>,
+    pub blogs: Blogs,
+}
+
+/// Any other fetched document worth keeping across visits, such as the
+/// GitHub repository listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cached<T> {
+    pub fetched_at: DateTime<Utc>,
+    pub value: T,
+}
+
+fn key(url: &str) -> String {
+    format!("{}{}", KEY_PREFIX, url)
+}
+
+fn restore<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let storage = StorageService::new(Area::Local).ok()?;
+    let Json(cached) = storage.restore::<Json<anyhow::Result<T>>>(key);
+    cached.ok()
+}
+
+/// Best effort: a full or disabled storage only costs the next visit a
+/// network round trip.
+fn save<T: Serialize>(key: &str, value: &T) {
+    if let Ok(mut storage) = StorageService::new(Area::Local) {
+        storage.store(key, Json(value));
+    }
+}
+
+pub fn load(url: &str) -> Option<CachedFeed> {
+    restore(&key(url))
+}
+
+pub fn store(url: &str, blogs: &Blogs) {
+    let cached = CachedFeed {
+        fetched_at: crate::util::now(),
+        blogs: blogs.clone(),
+    };
+    save(&key(url), &cached);
+}
+
+pub fn load_json<T: DeserializeOwned>(url: &str) -> Option<Cached<T>> {
+    restore(&format!("{}{}", JSON_PREFIX, url))
+}
+
+pub fn store_json<T: Serialize>(url: &str, value: &T) {
+    let cached = Cached {
+        fetched_at: crate::util::now(),
+        value,
+    };
+    save(&format!("{}{}", JSON_PREFIX, url), &cached);
+}
+
+#[test]
+fn test_cached_feed_roundtrip() {
+    let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    <url>
+        <loc>http://another-s347.github.io/blogs/a/</loc>
+        <lastmod>2021-01-05T11:00:30Z</lastmod>
+        <title>A</title>
+        <tag>Rust</tag>
+    </url>
+    <url>
+        <lastmod>yesterday</lastmod>
+    </url>
+</urlset>"#;
+    let cached = CachedFeed {
+        fetched_at: crate::util::from_millis(1_609_844_430_000),
+        blogs: super::feed::parse_feed(data).unwrap(),
+    };
+    let json = serde_json::to_string(&cached).unwrap();
+    let restored: CachedFeed = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.fetched_at, cached.fetched_at);
+    assert_eq!(restored.blogs.last_update, cached.blogs.last_update);
+    assert_eq!(restored.blogs.blogs.len(), 1);
+    assert_eq!(restored.blogs.blogs[0].tags, vec!["Rust".to_string()]);
+    assert_eq!(restored.blogs.warnings, cached.blogs.warnings);
+    assert_eq!(key("https://example.com/feed.xml"), "io-index.feed.v1:https://example.com/feed.xml");
+}
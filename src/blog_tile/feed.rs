@@ -0,0 +1,516 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use xml::{attribute::OwnedAttribute, name::OwnedName, reader::XmlEvent};
+
+use super::{Blog, Blogs};
+
+/// Formats a blog source may publish, detected from the root element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedFormat {
+    /// `<urlset>`, optionally with our non-standard `<title>`, `<summary>`,
+    /// `<tag>`, `<author>` and `<cover>` inside `<url>`, or the standard
+    /// `<image:image>` extension for the cover.
+    Sitemap,
+    /// RSS 2.0 `<rss>`, also accepting RSS 1.0 `<rdf:RDF>`.
+    Rss,
+    /// Atom `<feed>`.
+    Atom,
+}
+
+impl FeedFormat {
+    pub fn detect(root: &OwnedName) -> Option<Self> {
+        match root.local_name.as_str() {
+            "urlset" => Some(FeedFormat::Sitemap),
+            "rss" | "RDF" => Some(FeedFormat::Rss),
+            "feed" => Some(FeedFormat::Atom),
+            _ => None,
+        }
+    }
+
+    fn entry(self) -> &'static str {
+        match self {
+            FeedFormat::Sitemap => "url",
+            FeedFormat::Rss => "item",
+            FeedFormat::Atom => "entry",
+        }
+    }
+
+    fn state(self, name: &OwnedName) -> SetState {
+        match (self, name.prefix.as_deref(), name.local_name.as_str()) {
+            (_, None, "title") => SetState::Title,
+            (FeedFormat::Sitemap, None, "loc") | (FeedFormat::Rss, None, "link") => SetState::Loc,
+            (FeedFormat::Sitemap, None, "lastmod")
+            | (FeedFormat::Rss, None, "pubDate")
+            | (FeedFormat::Rss, Some("dc"), "date")
+            | (FeedFormat::Atom, None, "updated") => SetState::LastUpdate,
+            (FeedFormat::Rss, None, "description") | (FeedFormat::Atom, None, "summary") => {
+                SetState::Summary
+            }
+            (FeedFormat::Sitemap, None, "summary") => SetState::Summary,
+            (FeedFormat::Rss, None, "category") | (FeedFormat::Sitemap, None, "tag") => SetState::Tag,
+            (FeedFormat::Sitemap, None, "author")
+            | (FeedFormat::Rss, None, "author")
+            | (FeedFormat::Rss, Some("dc"), "creator")
+            | (FeedFormat::Atom, None, "name") => SetState::Author,
+            (FeedFormat::Sitemap, None, "cover") | (FeedFormat::Sitemap, Some("image"), "loc") => {
+                SetState::Cover
+            }
+            _ => SetState::Skip,
+        }
+    }
+}
+
+/// A problem found in one part of a feed that did not stop the rest of it
+/// from being parsed. `entry` is the zero-based index of the entry element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParseWarning {
+    InvalidLastUpdate { entry: usize, value: String },
+    MissingLink { entry: usize },
+    UnexpectedText(String),
+    UnmatchedEnd,
+    Malformed(String),
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::InvalidLastUpdate { entry, value } => {
+                write!(f, "entry {}: invalid date \"{}\"", entry + 1, value)
+            }
+            ParseWarning::MissingLink { entry } => {
+                write!(f, "entry {}: missing link, skipped", entry + 1)
+            }
+            ParseWarning::UnexpectedText(text) => write!(f, "unexpected text \"{}\"", text),
+            ParseWarning::UnmatchedEnd => write!(f, "end of entry without matching start"),
+            ParseWarning::Malformed(err) => write!(f, "document truncated: {}", err),
+        }
+    }
+}
+
+enum Parser {
+    Enter(usize, Blog, SetState),
+    Exit,
+}
+
+enum SetState {
+    Loc,
+    LastUpdate,
+    Title,
+    Summary,
+    Tag,
+    Author,
+    Cover,
+    Skip,
+    None,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Parser::Exit
+    }
+
+    pub fn begin(&mut self, entry: usize) {
+        *self = Parser::Enter(entry, Blog::default(), SetState::None);
+    }
+
+    pub fn is_entered(&self) -> bool {
+        matches!(self, Parser::Enter(..))
+    }
+
+    pub fn set(&mut self, data: String) -> Result<(), ParseWarning> {
+        match self {
+            Parser::Enter(entry, blog, state) => match std::mem::replace(state, SetState::None) {
+                SetState::Loc => {
+                    blog.link = data.trim().to_string();
+                }
+                SetState::LastUpdate => match parse_feed_datetime(&data) {
+                    Some(last_update) => blog.last_update = last_update,
+                    None => {
+                        return Err(ParseWarning::InvalidLastUpdate {
+                            entry: *entry,
+                            value: data.trim().to_string(),
+                        })
+                    }
+                },
+                SetState::Title => {
+                    blog.title = data.trim().to_string();
+                }
+                SetState::Summary => {
+                    blog.summary = plain_text(&data);
+                }
+                SetState::Tag => {
+                    push_tag(blog, &data);
+                }
+                SetState::Author => {
+                    if blog.author.is_empty() {
+                        blog.author = data.trim().to_string();
+                    }
+                }
+                SetState::Cover => {
+                    set_cover(blog, &data);
+                }
+                SetState::Skip => {}
+                SetState::None => return unexpected_text(data),
+            },
+            Parser::Exit => return unexpected_text(data),
+        }
+        Ok(())
+    }
+
+    pub fn enter(&mut self, state: SetState) {
+        if let Parser::Enter(_, _, s) = self {
+            *s = state;
+        }
+    }
+
+    /// Atom carries the entry link and categories in attributes rather than
+    /// text content, and both RSS and Atom attach cover images as enclosures
+    /// or Media RSS thumbnails.
+    pub fn attributes(&mut self, format: FeedFormat, name: &OwnedName, attributes: &[OwnedAttribute]) {
+        let blog = match self {
+            Parser::Enter(_, blog, _) => blog,
+            Parser::Exit => return,
+        };
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|a| a.name.local_name == key)
+                .map(|a| a.value.as_str())
+        };
+        let is_image = attribute("type").is_some_and(|t| t.starts_with("image/"))
+            || attribute("medium") == Some("image");
+        match (format, name.prefix.as_deref(), name.local_name.as_str()) {
+            (FeedFormat::Atom, None, "link") => match attribute("rel") {
+                None | Some("alternate") if blog.link.is_empty() => {
+                    blog.link = attribute("href").unwrap_or_default().trim().to_string();
+                }
+                Some("enclosure") if is_image => set_cover(blog, attribute("href").unwrap_or_default()),
+                _ => {}
+            },
+            (FeedFormat::Atom, None, "category") => push_tag(blog, attribute("term").unwrap_or_default()),
+            (FeedFormat::Rss, None, "enclosure") if is_image => {
+                set_cover(blog, attribute("url").unwrap_or_default())
+            }
+            (_, Some("media"), "thumbnail") => set_cover(blog, attribute("url").unwrap_or_default()),
+            (_, Some("media"), "content") if is_image => {
+                set_cover(blog, attribute("url").unwrap_or_default())
+            }
+            _ => {}
+        }
+    }
+
+    pub fn leave(&mut self) {
+        self.enter(SetState::None);
+    }
+
+    pub fn exit(&mut self) -> Option<(usize, Blog)> {
+        match std::mem::replace(self, Parser::Exit) {
+            Parser::Enter(entry, blog, _) => Some((entry, blog)),
+            Parser::Exit => None,
+        }
+    }
+}
+
+fn unexpected_text(data: String) -> Result<(), ParseWarning> {
+    let data = data.trim();
+    if data.is_empty() {
+        Ok(())
+    } else {
+        Err(ParseWarning::UnexpectedText(data.to_string()))
+    }
+}
+
+fn push_tag(blog: &mut Blog, tag: &str) {
+    let tag = tag.trim();
+    if !tag.is_empty() && !blog.tags.iter().any(|t| t == tag) {
+        blog.tags.push(tag.to_string());
+    }
+}
+
+fn set_cover(blog: &mut Blog, url: &str) {
+    let url = url.trim();
+    if blog.cover_image.is_none() && !url.is_empty() {
+        blog.cover_image = Some(url.to_string());
+    }
+}
+
+/// Sitemaps and Atom use W3C datetimes, RSS uses RFC 2822.
+fn parse_feed_datetime(value: &str) -> Option<DateTime<Utc>> {
+    crate::util::parse_w3c_datetime(value).or_else(|| {
+        DateTime::parse_from_rfc2822(value.trim())
+            .ok()
+            .map(|datetime| datetime.with_timezone(&Utc))
+    })
+}
+
+/// RSS descriptions are usually escaped HTML; cards only show plain text.
+fn plain_text(html: &str) -> String {
+    crate::util::decode_entities(&crate::util::strip_tags(html, " "))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses a sitemap, RSS or Atom document into `Blogs`, picking the format
+/// from the root element. Bad entries are kept partially or skipped and
+/// reported in `Blogs::warnings`; only a document that yields no entries at
+/// all before breaking is an error.
+pub fn parse_feed(data: &str) -> anyhow::Result<Blogs> {
+    let mut parser = Parser::new();
+    let mut format = None;
+    let mut depth = 0;
+    let mut ret = vec![];
+    let mut warnings = vec![];
+    let mut entries = 0;
+    for event in xml::reader::EventReader::new(data.as_bytes()) {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) if ret.is_empty() => return Err(err.into()),
+            Err(err) => {
+                warnings.push(ParseWarning::Malformed(err.to_string()));
+                break;
+            }
+        };
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                depth += 1;
+                let format = match format {
+                    Some(format) => format,
+                    None => {
+                        format = FeedFormat::detect(&name);
+                        format.ok_or_else(|| {
+                            anyhow::anyhow!("unsupported feed format <{}>", name.local_name)
+                        })?;
+                        continue;
+                    }
+                };
+                if name.prefix.is_none() && name.local_name == format.entry() {
+                    parser.begin(entries);
+                    entries += 1;
+                } else {
+                    parser.enter(format.state(&name));
+                    parser.attributes(format, &name, &attributes);
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                depth -= 1;
+                match format {
+                    Some(format) if name.prefix.is_none() && name.local_name == format.entry() => {
+                        match parser.exit() {
+                            Some((entry, blog)) if blog.link.is_empty() => {
+                                warnings.push(ParseWarning::MissingLink { entry });
+                            }
+                            Some((_, blog)) => ret.push(blog),
+                            None => warnings.push(ParseWarning::UnmatchedEnd),
+                        }
+                    }
+                    _ => parser.leave(),
+                }
+            }
+            XmlEvent::Characters(data) | XmlEvent::CData(data) => {
+                // Feed-level metadata such as the channel title lives outside
+                // entries and is not ours to report.
+                if depth > 1 && !parser.is_entered() {
+                    continue;
+                }
+                if let Err(warning) = parser.set(data) {
+                    warnings.push(warning);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let last_update = ret
+        .iter()
+        .map(|x| x.last_update)
+        .max()
+        .unwrap_or_else(crate::util::epoch);
+
+    Ok(Blogs {
+        last_update,
+        blogs: ret,
+        warnings,
+        meta: None,
+    })
+}
+
+#[test]
+fn test_parse_sitemap() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+      
+      <url>
+        <title>使用PyO3跨越编程语言的异步</title>
+        <loc>http://another-s347.github.io/blogs/2021/01/01/async-pyo3-1/</loc>
+        
+        <lastmod>1609859438225</lastmod>
+        
+      </url>
+      
+      <url>
+        <title></title>
+        <loc>http://another-s347.github.io/blogs/2021/01/05/dummy/</loc>
+        
+        <lastmod>1609859415394</lastmod>
+        
+      </url>
+      
+    
+      <!-- <url>
+        <loc>http://another-s347.github.io/blogs</loc>
+        <lastmod>2021-01-05</lastmod>
+        <changefreq>daily</changefreq>
+        <priority>1.0</priority>
+      </url>
+    
+      
+    
+       -->
+    </urlset>
+    "#;
+    println!("{:#?}", parse_feed(xml));
+}
+
+#[test]
+fn test_parse_sitemap_tolerant() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+      stray text
+      <url>
+        <title>ok</title>
+        <loc>http://another-s347.github.io/blogs/ok/</loc>
+        <lastmod>1609859438225</lastmod>
+      </url>
+      <url>
+        <title>bad date</title>
+        <loc>http://another-s347.github.io/blogs/bad-date/</loc>
+        <lastmod>yesterday</lastmod>
+      </url>
+      <url>
+        <title>w3c date</title>
+        <loc>http://another-s347.github.io/blogs/w3c-date/</loc>
+        <lastmod>2021-01-05</lastmod>
+      </url>
+      <url>
+        <title>no link</title>
+        <lastmod>1609859415394</lastmod>
+      </url>
+      <url>
+        <loc>http://another-s347.github.io/blogs/truncated/</loc>
+    "#;
+    let blogs = parse_feed(xml).unwrap();
+    assert_eq!(blogs.blogs.len(), 3);
+    assert_eq!(blogs.blogs[1].title, "bad date");
+    assert_eq!(blogs.blogs[2].last_update, Utc.with_ymd_and_hms(2021, 1, 5, 0, 0, 0).unwrap());
+    assert_eq!(blogs.last_update, crate::util::from_millis(1609859438225));
+    assert_eq!(
+        blogs.warnings[..3],
+        [
+            ParseWarning::UnexpectedText("stray text".to_string()),
+            ParseWarning::InvalidLastUpdate {
+                entry: 1,
+                value: "yesterday".to_string()
+            },
+            ParseWarning::MissingLink { entry: 3 },
+        ]
+    );
+    assert!(matches!(blogs.warnings[3], ParseWarning::Malformed(_)));
+
+    assert!(parse_feed("<html><body>Not Found").is_err());
+}
+
+#[test]
+fn test_parse_rss() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+      <channel>
+        <title>Blogs</title>
+        <link>http://another-s347.github.io/blogs/</link>
+        <description>channel description</description>
+        <item>
+          <title>使用PyO3跨越编程语言的异步</title>
+          <link>http://another-s347.github.io/blogs/2021/01/01/async-pyo3-1/</link>
+          <pubDate>Tue, 05 Jan 2021 15:10:38 +0000</pubDate>
+          <description><![CDATA[<p>Async across <b>languages</b> &amp; runtimes</p>]]></description>
+          <category>Rust</category>
+          <category>Python</category>
+          <dc:creator>skye347</dc:creator>
+          <enclosure url="http://another-s347.github.io/blogs/cover.png" type="image/png" length="0"/>
+          <guid>async-pyo3-1</guid>
+        </item>
+      </channel>
+    </rss>
+    "#;
+    let blogs = parse_feed(xml).unwrap();
+    assert!(blogs.warnings.is_empty(), "{:?}", blogs.warnings);
+    assert_eq!(blogs.blogs.len(), 1);
+    let blog = &blogs.blogs[0];
+    assert_eq!(blog.title, "使用PyO3跨越编程语言的异步");
+    assert_eq!(blog.link, "http://another-s347.github.io/blogs/2021/01/01/async-pyo3-1/");
+    assert_eq!(blog.last_update, Utc.with_ymd_and_hms(2021, 1, 5, 15, 10, 38).unwrap());
+    assert_eq!(blog.summary, "Async across languages & runtimes");
+    assert_eq!(blog.tags, vec!["Rust", "Python"]);
+    assert_eq!(blog.author, "skye347");
+    assert_eq!(blog.cover_image.as_deref(), Some("http://another-s347.github.io/blogs/cover.png"));
+}
+
+#[test]
+fn test_parse_atom() {
+    let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+    <feed xmlns="http://www.w3.org/2005/Atom">
+      <title>Blogs</title>
+      <link href="http://another-s347.github.io/blogs-en/"/>
+      <updated>2021-01-05T15:10:38Z</updated>
+      <entry>
+        <title>Async across languages with PyO3</title>
+        <link rel="self" href="http://another-s347.github.io/blogs-en/atom/1"/>
+        <link href="http://another-s347.github.io/blogs-en/2021/01/01/async-pyo3-1/"/>
+        <updated>2021-01-05T23:10:38+08:00</updated>
+        <author><name>skye347</name></author>
+        <summary>Bridging Rust and Python futures.</summary>
+        <category term="Rust"/>
+        <category term="Rust"/>
+      </entry>
+    </feed>
+    "#;
+    let blogs = parse_feed(xml).unwrap();
+    assert!(blogs.warnings.is_empty(), "{:?}", blogs.warnings);
+    assert_eq!(blogs.blogs.len(), 1);
+    let blog = &blogs.blogs[0];
+    assert_eq!(blog.link, "http://another-s347.github.io/blogs-en/2021/01/01/async-pyo3-1/");
+    assert_eq!(blog.last_update, Utc.with_ymd_and_hms(2021, 1, 5, 15, 10, 38).unwrap());
+    assert_eq!(blog.summary, "Bridging Rust and Python futures.");
+    assert_eq!(blog.tags, vec!["Rust"]);
+    assert_eq!(blog.author, "skye347");
+    assert_eq!(blog.cover_image, None);
+}
+
+#[test]
+fn test_parse_sitemap_extensions() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+            xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+      <url>
+        <title>使用PyO3跨越编程语言的异步</title>
+        <loc>http://another-s347.github.io/blogs/2021/01/01/async-pyo3-1/</loc>
+        <lastmod>1609859438225</lastmod>
+        <summary>跨越 Rust 与 Python 的异步</summary>
+        <tag>Rust</tag>
+        <tag>PyO3</tag>
+        <author>skye347</author>
+        <image:image>
+          <image:loc>http://another-s347.github.io/blogs/cover.png</image:loc>
+        </image:image>
+      </url>
+    </urlset>
+    "#;
+    let blogs = parse_feed(xml).unwrap();
+    assert!(blogs.warnings.is_empty(), "{:?}", blogs.warnings);
+    let blog = &blogs.blogs[0];
+    assert_eq!(blog.link, "http://another-s347.github.io/blogs/2021/01/01/async-pyo3-1/");
+    assert_eq!(blog.summary, "跨越 Rust 与 Python 的异步");
+    assert_eq!(blog.tags, vec!["Rust", "PyO3"]);
+    assert_eq!(blog.author, "skye347");
+    assert_eq!(blog.cover_image.as_deref(), Some("http://another-s347.github.io/blogs/cover.png"));
+}
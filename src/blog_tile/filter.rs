@@ -0,0 +1,136 @@
+use chrono::NaiveDate;
+
+use super::Blog;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SortOrder {
+    #[default]
+    Newest,
+    Oldest,
+    Title,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 3] = [SortOrder::Newest, SortOrder::Oldest, SortOrder::Title];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Newest => "newest",
+            SortOrder::Oldest => "oldest",
+            SortOrder::Title => "title",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|order| order.as_str() == value)
+    }
+}
+
+/// How a tile orders and narrows its entries. The state round-trips through
+/// the hash query as `sort`, `tag`, `from` and `to`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlogFilter {
+    pub sort: SortOrder,
+    pub tag: Option<String>,
+    /// Inclusive bounds on the UTC date of `last_update`.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl BlogFilter {
+    pub fn apply<'a>(&self, blogs: &'a [Blog]) -> Vec<&'a Blog> {
+        let mut ret = blogs
+            .iter()
+            .filter(|blog| {
+                let date = blog.last_update.date_naive();
+                self.tag.as_ref().is_none_or(|tag| blog.tags.contains(tag))
+                    && self.from.is_none_or(|from| date >= from)
+                    && self.to.is_none_or(|to| date <= to)
+            })
+            .collect::<Vec<_>>();
+        match self.sort {
+            SortOrder::Newest => ret.sort_by_key(|blog| std::cmp::Reverse(blog.last_update)),
+            SortOrder::Oldest => ret.sort_by_key(|blog| blog.last_update),
+            SortOrder::Title => ret.sort_by_cached_key(|blog| blog.title.to_lowercase()),
+        }
+        ret
+    }
+
+    /// Reads the state from query parameters, falling back to `default` for
+    /// the sort order.
+    pub fn from_query(default: SortOrder, get: impl Fn(&str) -> Option<String>) -> Self {
+        let date = |key| get(key).and_then(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d").ok());
+        Self {
+            sort: get("sort").and_then(|v| SortOrder::parse(&v)).unwrap_or(default),
+            tag: get("tag").filter(|tag| !tag.is_empty()),
+            from: date("from"),
+            to: date("to"),
+        }
+    }
+
+    /// Query parameters for this state; `None` removes a parameter.
+    pub fn to_query(&self, default: SortOrder) -> Vec<(&'static str, Option<String>)> {
+        let date = |d: Option<NaiveDate>| d.map(|d| d.format("%Y-%m-%d").to_string());
+        vec![
+            ("sort", Some(self.sort).filter(|s| *s != default).map(|s| s.as_str().to_string())),
+            ("tag", self.tag.clone()),
+            ("from", date(self.from)),
+            ("to", date(self.to)),
+        ]
+    }
+}
+
+/// Every tag used by `blogs`, in order of first appearance.
+pub fn all_tags(blogs: &[Blog]) -> Vec<&str> {
+    let mut tags: Vec<&str> = vec![];
+    for tag in blogs.iter().flat_map(|blog| &blog.tags) {
+        if !tags.contains(&tag.as_str()) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[test]
+fn test_filter_blogs() {
+    use super::blog;
+    let day = 86_400_000;
+    let blogs = vec![
+        blog("b", &["Rust"], day),
+        blog("C", &["Python"], 3 * day),
+        blog("a", &["Rust", "Python"], 2 * day),
+    ];
+    let titles = |filter: &BlogFilter| filter.apply(&blogs).iter().map(|b| b.title.clone()).collect::<Vec<_>>();
+
+    assert_eq!(titles(&BlogFilter::default()), vec!["C", "a", "b"]);
+    let mut filter = BlogFilter {
+        sort: SortOrder::Oldest,
+        ..BlogFilter::default()
+    };
+    assert_eq!(titles(&filter), vec!["b", "a", "C"]);
+    filter.sort = SortOrder::Title;
+    assert_eq!(titles(&filter), vec!["a", "b", "C"]);
+    filter.tag = Some("Rust".to_string());
+    assert_eq!(titles(&filter), vec!["a", "b"]);
+    filter.tag = None;
+    filter.from = NaiveDate::from_ymd_opt(1970, 1, 3);
+    filter.to = NaiveDate::from_ymd_opt(1970, 1, 3);
+    assert_eq!(titles(&filter), vec!["a"]);
+    assert_eq!(all_tags(&blogs), vec!["Rust", "Python"]);
+}
+
+#[test]
+fn test_filter_query() {
+    let filter = BlogFilter {
+        sort: SortOrder::Title,
+        tag: Some("Rust".to_string()),
+        from: NaiveDate::from_ymd_opt(2021, 1, 5),
+        to: None,
+    };
+    let query = filter.to_query(SortOrder::Newest);
+    let get = |key: &str| query.iter().find(|(k, _)| *k == key).and_then(|(_, v)| v.clone());
+    assert_eq!(get("sort").as_deref(), Some("title"));
+    assert_eq!(get("from").as_deref(), Some("2021-01-05"));
+    assert_eq!(BlogFilter::from_query(SortOrder::Newest, get), filter);
+    assert_eq!(BlogFilter::from_query(SortOrder::Oldest, |_| None).sort, SortOrder::Oldest);
+}
@@ -0,0 +1,419 @@
+use std::{cell::RefCell, fmt, rc::Rc, time::Duration};
+
+use chrono::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortController, AbortSignal, RequestCache, RequestInit, Response};
+use yew::{
+    prelude::*,
+    services::timeout::{TimeoutService, TimeoutTask},
+    services::{ConsoleService, Task},
+};
+
+use super::{feed, Blogs};
+
+/// Attempts per fetch, including the first one.
+const MAX_ATTEMPTS: u32 = 4;
+/// Wait before the first retry, doubled for every further one.
+const BACKOFF: Duration = Duration::from_secs(1);
+/// An attempt that has not answered by then counts as failed.
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Why a feed could not be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// The request could not be sent or the connection failed, e.g. offline
+    /// or blocked by CORS.
+    Network(String),
+    /// No response within `TIMEOUT`.
+    Timeout,
+    /// The server answered with a non-success status.
+    Status(u16),
+    /// The server answered with something other than a feed, e.g. an HTML
+    /// error page; holds the `Content-Type`.
+    ContentType(String),
+    /// The response is not a feed we can read.
+    Parse(String),
+}
+
+impl FetchError {
+    /// Whether trying again later may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Network(_) | FetchError::Timeout => true,
+            FetchError::Status(status) => *status >= 500 || *status == 408 || *status == 429,
+            FetchError::ContentType(_) | FetchError::Parse(_) => false,
+        }
+    }
+
+    /// Message for the page, in the reader's language.
+    pub fn describe(&self, lang: &crate::lang::Language) -> String {
+        let mut args = fluent_bundle::FluentArgs::new();
+        let id = match self {
+            FetchError::Network(detail) => {
+                args.set("detail", detail.as_str());
+                "fetch-error-network"
+            }
+            FetchError::Timeout => "fetch-error-timeout",
+            FetchError::Status(status) => {
+                args.set("status", *status);
+                "fetch-error-status"
+            }
+            FetchError::ContentType(mime) => {
+                args.set("type", mime.as_str());
+                "fetch-error-content-type"
+            }
+            FetchError::Parse(detail) => {
+                args.set("detail", detail.as_str());
+                "fetch-error-parse"
+            }
+        };
+        lang.format(id, Some(&args))
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network(detail) => write!(f, "network error: {}", detail),
+            FetchError::Timeout => write!(f, "timed out after {}s", TIMEOUT.as_secs()),
+            FetchError::Status(status) => write!(f, "HTTP status {}", status),
+            FetchError::ContentType(mime) => write!(f, "not a feed: {}", mime),
+            FetchError::Parse(detail) => write!(f, "invalid feed: {}", detail),
+        }
+    }
+}
+
+/// What the server said when a feed was fetched, kept on `Blogs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseMeta {
+    pub status: u16,
+    /// The URL the feed was served from, after redirects.
+    pub url: String,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A post's source as served, for the reader to render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    /// The URL the document was served from, after redirects; relative
+    /// links in it resolve against this.
+    pub url: String,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+/// Delay before attempt `attempt` (1-based, so the first retry is 2).
+fn backoff(attempt: u32) -> Duration {
+    BACKOFF * 2u32.saturating_pow(attempt.saturating_sub(2))
+}
+
+/// Checks and parses a response; gets the time it arrived.
+type Reader<T> = fn(RawResponse, DateTime<Utc>) -> Result<T, FetchError>;
+
+struct PendingRequest<T> {
+    url: String,
+    revalidate: bool,
+    read: Reader<T>,
+    on_done: Callback<Result<T, FetchError>>,
+    /// The attempt currently waiting for a response, if any; answers to
+    /// other attempts are stale and dropped.
+    pending: Option<u32>,
+    abort: Option<AbortController>,
+    timer: Option<TimeoutTask>,
+}
+
+impl<T> PendingRequest<T> {
+    fn cancel(&mut self) {
+        self.pending = None;
+        if let Some(abort) = self.abort.take() {
+            abort.abort();
+        }
+        self.timer = None;
+    }
+}
+
+/// A fetch in progress, including the waits between retries. Dropping it
+/// cancels whatever is pending and nothing is reported.
+pub struct RetryTask<T>(Rc<RefCell<PendingRequest<T>>>);
+
+pub type FeedTask = RetryTask<Blogs>;
+
+impl<T> Task for RetryTask<T> {
+    fn is_active(&self) -> bool {
+        let request = self.0.borrow();
+        request.pending.is_some() || request.timer.is_some()
+    }
+}
+
+impl<T> Drop for RetryTask<T> {
+    fn drop(&mut self) {
+        self.0.borrow_mut().cancel();
+    }
+}
+
+/// Fetches and parses one feed, handing the outcome to `on_done`.
+/// Transient failures are retried with exponential backoff before they are
+/// reported. With `revalidate` the browser checks its cached copy with the
+/// server (`ETag`/`Last-Modified`) rather than serving it as is.
+///
+/// Components should go through `store::FeedStore`, which shares one fetch
+/// per URL between them.
+pub fn fetch_feed(url: &str, revalidate: bool, on_done: Callback<Result<Blogs, FetchError>>) -> FeedTask {
+    fetch(url, revalidate, read_feed, on_done)
+}
+
+/// Fetches a JSON document such as a GitHub API listing, with the same
+/// retries and errors as `fetch_feed`.
+pub fn fetch_json<T: DeserializeOwned + 'static>(url: &str, on_done: Callback<Result<T, FetchError>>) -> RetryTask<T> {
+    fetch(url, false, read_json::<T>, on_done)
+}
+
+/// Fetches the HTML or Markdown source of a post, with the same retries and
+/// errors as `fetch_feed`.
+pub fn fetch_document(url: &str, on_done: Callback<Result<Document, FetchError>>) -> RetryTask<Document> {
+    fetch(url, false, read_document, on_done)
+}
+
+fn fetch<T: 'static>(url: &str, revalidate: bool, read: Reader<T>, on_done: Callback<Result<T, FetchError>>) -> RetryTask<T> {
+    let request = Rc::new(RefCell::new(PendingRequest {
+        url: url.to_string(),
+        revalidate,
+        read,
+        on_done,
+        pending: None,
+        abort: None,
+        timer: None,
+    }));
+    start(&request, 1);
+    RetryTask(request)
+}
+
+fn start<T: 'static>(request: &Rc<RefCell<PendingRequest<T>>>, attempt: u32) {
+    let (url, revalidate, read) = {
+        let request = request.borrow();
+        (request.url.clone(), request.revalidate, request.read)
+    };
+    ConsoleService::log(&format!("fetch {} (attempt {})", url, attempt));
+    let abort = AbortController::new().ok();
+    let signal = abort.as_ref().map(AbortController::signal);
+    let weak = Rc::downgrade(request);
+    wasm_bindgen_futures::spawn_local(async move {
+        let response = get(&url, revalidate, signal.as_ref()).await;
+        if let Some(request) = weak.upgrade() {
+            if request.borrow().pending == Some(attempt) {
+                finish(&request, attempt, response.and_then(|r| read(r, crate::util::now())));
+            }
+        }
+    });
+    let weak = Rc::downgrade(request);
+    let timer = TimeoutService::spawn(
+        TIMEOUT,
+        Callback::from(move |_| {
+            if let Some(request) = weak.upgrade() {
+                finish(&request, attempt, Err(FetchError::Timeout));
+            }
+        }),
+    );
+    let mut request = request.borrow_mut();
+    request.pending = Some(attempt);
+    request.abort = abort;
+    request.timer = Some(timer);
+}
+
+/// Ends an attempt: drops whichever of the fetch and its timeout is still
+/// pending, then either schedules the next attempt or reports.
+fn finish<T: 'static>(request: &Rc<RefCell<PendingRequest<T>>>, attempt: u32, result: Result<T, FetchError>) {
+    request.borrow_mut().cancel();
+    match result {
+        Err(err) if err.is_transient() && attempt < MAX_ATTEMPTS => {
+            ConsoleService::warn(&format!("{}, retrying", err));
+            let weak = Rc::downgrade(request);
+            let timer = TimeoutService::spawn(
+                backoff(attempt + 1),
+                Callback::from(move |_| {
+                    if let Some(request) = weak.upgrade() {
+                        start(&request, attempt + 1);
+                    }
+                }),
+            );
+            request.borrow_mut().timer = Some(timer);
+        }
+        result => {
+            let on_done = request.borrow().on_done.clone();
+            on_done.emit(result);
+        }
+    }
+}
+
+/// One answer from the server, before it is checked and parsed.
+struct RawResponse {
+    status: u16,
+    url: String,
+    content_type: Option<String>,
+    body: String,
+}
+
+async fn get(url: &str, revalidate: bool, signal: Option<&AbortSignal>) -> Result<RawResponse, FetchError> {
+    let window = web_sys::window().ok_or_else(|| FetchError::Network("no window".to_string()))?;
+    let init = RequestInit::new();
+    init.set_method("GET");
+    init.set_signal(signal);
+    if revalidate {
+        init.set_cache(RequestCache::NoCache);
+    }
+    let response: Response = JsFuture::from(window.fetch_with_str_and_init(url, &init))
+        .await
+        .map_err(js_error)?
+        .unchecked_into();
+    // Error pages are not worth downloading.
+    let body = if response.ok() {
+        JsFuture::from(response.text().map_err(js_error)?)
+            .await
+            .map_err(js_error)?
+            .as_string()
+            .unwrap_or_default()
+    } else {
+        String::new()
+    };
+    Ok(RawResponse {
+        status: response.status(),
+        url: response.url(),
+        content_type: response.headers().get("content-type").ok().flatten(),
+        body,
+    })
+}
+
+fn js_error(err: JsValue) -> FetchError {
+    let message = match err.dyn_ref::<js_sys::Error>() {
+        Some(err) => String::from(err.message()),
+        None => format!("{:?}", err),
+    };
+    FetchError::Network(message)
+}
+
+/// Whether a `Content-Type` may hold a feed. Static hosts label XML
+/// inconsistently, so anything XML-ish, plain text and unlabeled bytes are
+/// handed to the parser; HTML, JSON, images and the like are not.
+fn is_feed_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    mime.is_empty() || mime.ends_with("xml") || mime == "text/plain" || mime == "application/octet-stream"
+}
+
+/// Rejects error statuses and, when the server labels the body, types
+/// `accept` does not allow.
+fn check(response: &RawResponse, accept: fn(&str) -> bool) -> Result<(), FetchError> {
+    if !(200..300).contains(&response.status) {
+        return Err(FetchError::Status(response.status));
+    }
+    match &response.content_type {
+        Some(content_type) if !accept(content_type) => Err(FetchError::ContentType(content_type.clone())),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a `Content-Type` may hold a post: HTML, Markdown or plain text.
+/// Unlabeled bytes are let through like for feeds.
+fn is_document_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    mime.is_empty()
+        || mime.contains("html")
+        || mime.contains("markdown")
+        || mime == "text/plain"
+        || mime == "application/octet-stream"
+}
+
+fn read_json<T: DeserializeOwned>(response: RawResponse, _: DateTime<Utc>) -> Result<T, FetchError> {
+    check(&response, |content_type| content_type.contains("json"))?;
+    serde_json::from_str(&response.body).map_err(|err| FetchError::Parse(err.to_string()))
+}
+
+fn read_feed(response: RawResponse, fetched_at: DateTime<Utc>) -> Result<Blogs, FetchError> {
+    check(&response, is_feed_type)?;
+    let mut blogs = feed::parse_feed(&response.body).map_err(|err| FetchError::Parse(err.to_string()))?;
+    blogs.meta = Some(ResponseMeta {
+        status: response.status,
+        url: response.url,
+        fetched_at,
+    });
+    Ok(blogs)
+}
+
+fn read_document(response: RawResponse, _: DateTime<Utc>) -> Result<Document, FetchError> {
+    check(&response, is_document_type)?;
+    Ok(Document {
+        url: response.url,
+        content_type: response.content_type,
+        body: response.body,
+    })
+}
+
+#[test]
+fn test_fetch_error() {
+    assert!(FetchError::Network("offline".to_string()).is_transient());
+    assert!(FetchError::Timeout.is_transient());
+    assert!(FetchError::Status(503).is_transient());
+    assert!(FetchError::Status(429).is_transient());
+    assert!(!FetchError::Status(404).is_transient());
+    assert!(!FetchError::Parse("no entries".to_string()).is_transient());
+    assert_eq!(backoff(2), Duration::from_secs(1));
+    assert_eq!(backoff(3), Duration::from_secs(2));
+    assert_eq!(backoff(4), Duration::from_secs(4));
+    let en = crate::lang::Language::from_lang("en");
+    assert_eq!(FetchError::Status(500).describe(&en), "The server responded with status 500");
+    assert_eq!(FetchError::Status(404).describe(&en), "Feed not found (404)");
+}
+
+#[test]
+fn test_read_response() {
+    let response = |status, content_type: Option<&str>, body: &str| RawResponse {
+        status,
+        url: "https://example.com/blogs/sitemap.xml".to_string(),
+        content_type: content_type.map(str::to_string),
+        body: body.to_string(),
+    };
+    let sitemap = r#"<urlset><url><loc>https://example.com/a/</loc><lastmod>2021-01-05</lastmod></url></urlset>"#;
+    let fetched_at = crate::util::from_millis(1_609_844_430_000);
+    let blogs = read_feed(response(200, Some("application/xml; charset=utf-8"), sitemap), fetched_at).unwrap();
+    assert_eq!(blogs.blogs.len(), 1);
+    assert_eq!(
+        blogs.meta,
+        Some(ResponseMeta {
+            status: 200,
+            url: "https://example.com/blogs/sitemap.xml".to_string(),
+            fetched_at,
+        })
+    );
+    assert!(read_feed(response(200, None, sitemap), fetched_at).is_ok());
+    assert!(read_feed(response(200, Some("text/plain"), sitemap), fetched_at).is_ok());
+    assert_eq!(
+        read_feed(response(404, Some("text/html"), ""), fetched_at).unwrap_err(),
+        FetchError::Status(404)
+    );
+    assert_eq!(
+        read_feed(response(200, Some("text/html; charset=utf-8"), "<html></html>"), fetched_at).unwrap_err(),
+        FetchError::ContentType("text/html; charset=utf-8".to_string())
+    );
+    assert!(matches!(
+        read_feed(response(200, Some("text/xml"), "<html></html>"), fetched_at),
+        Err(FetchError::Parse(_))
+    ));
+    let listing = read_json::<Vec<String>>(response(200, Some("application/json"), r#"["a", "b"]"#), fetched_at);
+    assert_eq!(listing.unwrap(), vec!["a".to_string(), "b".to_string()]);
+    assert!(matches!(
+        read_json::<Vec<String>>(response(200, Some("application/json"), "{}"), fetched_at),
+        Err(FetchError::Parse(_))
+    ));
+    assert_eq!(
+        read_json::<Vec<String>>(response(403, Some("application/json"), ""), fetched_at).unwrap_err(),
+        FetchError::Status(403)
+    );
+    let post = read_document(response(200, Some("text/markdown"), "# Hi"), fetched_at).unwrap();
+    assert_eq!(post.body, "# Hi");
+    assert!(read_document(response(200, Some("text/html"), "<html></html>"), fetched_at).is_ok());
+    assert_eq!(
+        read_document(response(200, Some("image/png"), ""), fetched_at).unwrap_err(),
+        FetchError::ContentType("image/png".to_string())
+    );
+}
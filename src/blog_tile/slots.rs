@@ -0,0 +1,145 @@
+use yew::{prelude::*, services::ConsoleService};
+
+use super::store::{FeedState, FeedStore, Request};
+use super::{merge_blogs, Blogs};
+use crate::config::FeedSource;
+use crate::lang::Language;
+
+pub enum FeedStatus {
+    Fetching,
+    Done(Blogs),
+    Err(String),
+}
+
+/// The feeds one tile shows: its bridge to the `FeedStore`, one slot per
+/// watched source filled as the store reports, and what they add up to.
+pub struct FeedSlots {
+    store: Box<dyn Bridge<FeedStore>>,
+    /// Watched sources with their index in `sources.json`.
+    sources: Vec<(usize, &'static FeedSource)>,
+    feeds: Vec<Option<FeedState>>,
+    status: FeedStatus,
+}
+
+impl FeedSlots {
+    pub fn new(callback: Callback<FeedState>) -> Self {
+        Self {
+            store: FeedStore::bridge(callback),
+            sources: Vec::new(),
+            feeds: Vec::new(),
+            status: FeedStatus::Fetching,
+        }
+    }
+
+    pub fn status(&self) -> &FeedStatus {
+        &self.status
+    }
+
+    /// Replaces the watched sources, emptying every slot. Without any the
+    /// status is the `no_source` error.
+    pub fn watch(&mut self, sources: Vec<(usize, &'static FeedSource)>, no_source: String) {
+        self.status = if sources.is_empty() {
+            FeedStatus::Err(no_source)
+        } else {
+            FeedStatus::Fetching
+        };
+        self.feeds = sources.iter().map(|_| None).collect();
+        self.store.send(Request::Watch(sources.iter().map(|(_, source)| source.url.clone()).collect()));
+        self.sources = sources;
+    }
+
+    /// Fills the slots of `state.url`; `false` if nothing changed. States
+    /// are keyed by URL, so those still queued for sources watched before
+    /// match no slot and are dropped.
+    pub fn update(&mut self, state: FeedState, language: &Language) -> bool {
+        let mut changed = false;
+        for ((_, source), current) in self.sources.iter().zip(self.feeds.iter_mut()) {
+            if source.url == state.url && !current.as_ref().is_some_and(|c| c.same_as(&state)) {
+                *current = Some(state.clone());
+                changed = true;
+            }
+        }
+        if !changed {
+            return false;
+        }
+        // A failed poll or revalidation keeps showing what was fetched
+        // before.
+        if let (Some(_), Some(err)) = (&state.blogs, &state.error) {
+            ConsoleService::warn(&err.to_string());
+        }
+        self.refresh_status(language);
+        true
+    }
+
+    /// Fetches the failed sources again, leaving the others as they are.
+    pub fn retry(&mut self, language: &Language) {
+        for feed in self.feeds.iter_mut().flatten() {
+            if feed.blogs.is_none() && feed.error.take().is_some() {
+                self.store.send(Request::Fetch { url: feed.url.clone(), revalidate: false });
+            }
+        }
+        self.refresh_status(language);
+    }
+
+    /// Revalidates every watched source in the background.
+    pub fn poll(&mut self) {
+        for (_, source) in &self.sources {
+            self.store.send(Request::Fetch { url: source.url.clone(), revalidate: true });
+        }
+    }
+
+    /// Shows whatever has arrived so far; the tile is only an error once
+    /// every source has failed.
+    fn refresh_status(&mut self, language: &Language) {
+        let done = self
+            .feeds
+            .iter()
+            .zip(&self.sources)
+            .filter_map(|(feed, (index, _))| Some((*index, &**feed.as_ref()?.blogs.as_ref()?)))
+            .collect::<Vec<_>>();
+        let failed = |feed: &Option<FeedState>| matches!(feed, Some(FeedState { error: Some(_), .. }));
+        self.status = if !done.is_empty() {
+            FeedStatus::Done(merge_blogs(&done))
+        } else if self.feeds.iter().all(failed) {
+            FeedStatus::Err(self.source_errors(language).join("; "))
+        } else {
+            FeedStatus::Fetching
+        };
+    }
+
+    /// One line per source that failed without anything to show.
+    pub fn source_errors(&self, language: &Language) -> Vec<String> {
+        self.feeds
+            .iter()
+            .zip(&self.sources)
+            .filter_map(|(feed, (_, source))| match feed {
+                Some(FeedState { blogs: None, error: Some(err), .. }) => {
+                    Some(format!("{}: {}", source.name, err.describe(language)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Shown once revalidating cached entries failed, with the age of the
+    /// oldest cached source still on screen.
+    pub fn render_cached_notice(&self, language: &Language) -> Html {
+        let oldest = self
+            .feeds
+            .iter()
+            .flatten()
+            .filter(|feed| feed.error.is_some())
+            .filter_map(|feed| feed.cached_at)
+            .min();
+        match oldest {
+            Some(fetched_at) => {
+                let mut args = fluent_bundle::FluentArgs::new();
+                args.set("when", language.relative_time(crate::util::now() - fetched_at));
+                html! {
+                    <p class="help is-warning">{language.format("cached-notice", Some(&args))}</p>
+                }
+            }
+            _ => html! {},
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+
+use chrono::prelude::*;
+use yew::agent::{Agent, AgentLink, Context, HandlerId};
+use yew::services::ConsoleService;
+
+use super::{cache, fetch_feed, Blogs, FeedTask, FetchError};
+
+/// What the store knows about one feed URL, sent to every component
+/// watching it whenever it changes.
+#[derive(Debug, Clone)]
+pub struct FeedState {
+    pub url: String,
+    /// Latest entries, fetched or restored from the offline cache.
+    pub blogs: Option<Rc<Blogs>>,
+    /// Set while `blogs` comes from the offline cache and has not been
+    /// revalidated yet: when that copy was fetched.
+    pub cached_at: Option<DateTime<Utc>>,
+    /// Why the latest fetch failed, if it did.
+    pub error: Option<FetchError>,
+}
+
+impl FeedState {
+    /// Whether both describe the same data, without comparing entries.
+    pub fn same_as(&self, other: &FeedState) -> bool {
+        let same_blogs = match (&self.blogs, &other.blogs) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_blogs && self.cached_at == other.cached_at && self.error == other.error
+    }
+}
+
+pub enum Request {
+    /// Replaces the URLs the sender watches. Feeds the store does not have
+    /// yet are restored from the offline cache and fetched.
+    Watch(Vec<String>),
+    /// Fetches a watched feed again, e.g. to retry or poll; a no-op while
+    /// it is already being fetched. `revalidate` as in `fetch_feed`.
+    Fetch { url: String, revalidate: bool },
+}
+
+pub enum Msg {
+    Done(String, Result<Blogs, FetchError>),
+}
+
+struct Feed<T> {
+    state: FeedState,
+    task: Option<T>,
+}
+
+/// Which feeds there are, what is being fetched for them and who watches
+/// them, apart from the agent plumbing. In the app `K` is a `HandlerId` and
+/// `T` a `FeedTask`.
+struct Feeds<K, T> {
+    feeds: HashMap<String, Feed<T>>,
+    watchers: HashMap<K, Vec<String>>,
+}
+
+impl<K: Copy + Eq + Hash, T> Feeds<K, T> {
+    fn new() -> Self {
+        Self {
+            feeds: HashMap::new(),
+            watchers: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, state: FeedState, task: Option<T>) {
+        self.feeds.insert(state.url.clone(), Feed { state, task });
+    }
+
+    /// Replaces the URLs `id` watches. Returns the URLs whose fetch was
+    /// aborted because nobody watches them any more.
+    fn watch(&mut self, id: K, urls: Vec<String>) -> Vec<String> {
+        self.watchers.insert(id, urls);
+        self.release_unwatched()
+    }
+
+    fn unwatch(&mut self, id: K) -> Vec<String> {
+        self.watchers.remove(&id);
+        self.release_unwatched()
+    }
+
+    /// Aborts fetches nobody waits for any more, e.g. for the feeds of a
+    /// language the reader has just switched away from.
+    fn release_unwatched(&mut self) -> Vec<String> {
+        let watched = self.watchers.values().flatten().collect::<HashSet<_>>();
+        let mut aborted = Vec::new();
+        for (url, feed) in &mut self.feeds {
+            if !watched.contains(url) && feed.task.take().is_some() {
+                aborted.push(url.clone());
+            }
+        }
+        aborted
+    }
+
+    /// Records the outcome of a fetch of `url`. Returns whether its state
+    /// changed, i.e. whether the watchers need to hear about it.
+    fn complete(&mut self, url: &str, result: Result<Blogs, FetchError>) -> bool {
+        let feed = match self.feeds.get_mut(url) {
+            Some(feed) => feed,
+            None => return false,
+        };
+        feed.task = None;
+        let state = &mut feed.state;
+        match result {
+            Ok(blogs) => {
+                let unchanged = state.cached_at.is_none()
+                    && state.error.is_none()
+                    && state.blogs.as_ref().map(|b| b.last_update) == Some(blogs.last_update);
+                if unchanged {
+                    return false;
+                }
+                state.blogs = Some(Rc::new(blogs));
+                state.cached_at = None;
+                state.error = None;
+            }
+            Err(err) => state.error = Some(err),
+        }
+        true
+    }
+
+    /// Who currently watches `url`.
+    fn watchers_of(&self, url: &str) -> Vec<K> {
+        self.watchers
+            .iter()
+            .filter(|(_, urls)| urls.iter().any(|u| u == url))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// Fetches each feed URL once for all tiles, search and whatever else shows
+/// feeds on the page, and keeps the result while any of them is mounted.
+pub struct FeedStore {
+    link: AgentLink<Self>,
+    feeds: Feeds<HandlerId, FeedTask>,
+}
+
+impl Agent for FeedStore {
+    type Reach = Context<Self>;
+    type Message = Msg;
+    type Input = Request;
+    type Output = FeedState;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        Self { link, feeds: Feeds::new() }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match msg {
+            Msg::Done(url, result) => {
+                if let (Ok(blogs), true) = (&result, self.feeds.feeds.contains_key(&url)) {
+                    cache::store(&url, blogs);
+                }
+                if self.feeds.complete(&url, result) {
+                    self.notify(&url);
+                }
+            }
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match msg {
+            Request::Watch(urls) => {
+                for url in &urls {
+                    match self.feeds.feeds.get(url) {
+                        Some(feed) => {
+                            self.link.respond(id, feed.state.clone());
+                            // Picks up fetches `release_unwatched` aborted.
+                            let state = &feed.state;
+                            let incomplete = state.blogs.is_none() || state.cached_at.is_some();
+                            if feed.task.is_none() && state.error.is_none() && incomplete {
+                                let task = Some(self.fetch(url, false));
+                                if let Some(feed) = self.feeds.feeds.get_mut(url) {
+                                    feed.task = task;
+                                }
+                            }
+                        }
+                        None => {
+                            let cached = cache::load(url);
+                            let state = FeedState {
+                                url: url.clone(),
+                                cached_at: cached.as_ref().map(|c| c.fetched_at),
+                                blogs: cached.map(|c| Rc::new(c.blogs)),
+                                error: None,
+                            };
+                            if state.blogs.is_some() {
+                                self.link.respond(id, state.clone());
+                            }
+                            let task = Some(self.fetch(url, false));
+                            self.feeds.insert(state, task);
+                        }
+                    }
+                }
+                let aborted = self.feeds.watch(id, urls);
+                log_aborted(&aborted);
+            }
+            Request::Fetch { url, revalidate } => {
+                let idle = matches!(self.feeds.feeds.get(&url), Some(feed) if feed.task.is_none());
+                if idle {
+                    let task = Some(self.fetch(&url, revalidate));
+                    if let Some(feed) = self.feeds.feeds.get_mut(&url) {
+                        feed.task = task;
+                    }
+                }
+            }
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        let aborted = self.feeds.unwatch(id);
+        log_aborted(&aborted);
+    }
+}
+
+fn log_aborted(urls: &[String]) {
+    for url in urls {
+        ConsoleService::log(&format!("abort fetch {}", url));
+    }
+}
+
+impl FeedStore {
+    fn fetch(&self, url: &str, revalidate: bool) -> FeedTask {
+        let key = url.to_string();
+        fetch_feed(url, revalidate, self.link.callback(move |result| Msg::Done(key.clone(), result)))
+    }
+
+    fn notify(&self, url: &str) {
+        if let Some(feed) = self.feeds.feeds.get(url) {
+            for id in self.feeds.watchers_of(url) {
+                self.link.respond(id, feed.state.clone());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_feed_state_same_as() {
+    let blogs = Rc::new(Blogs {
+        last_update: crate::util::epoch(),
+        blogs: vec![],
+        warnings: vec![],
+        meta: None,
+    });
+    let state = FeedState {
+        url: "https://example.com/feed.xml".to_string(),
+        blogs: Some(blogs.clone()),
+        cached_at: Some(crate::util::epoch()),
+        error: None,
+    };
+    assert!(state.same_as(&state.clone()));
+    let revalidated = FeedState { cached_at: None, ..state.clone() };
+    assert!(!state.same_as(&revalidated));
+    let offline = FeedState { error: Some(FetchError::Timeout), ..state.clone() };
+    assert!(!state.same_as(&offline));
+    let refetched = FeedState { blogs: Some(Rc::new((*blogs).clone())), ..state.clone() };
+    assert!(!state.same_as(&refetched));
+}
+
+#[test]
+fn test_release_unwatched() {
+    /// Stands in for a `FeedTask`, which aborts its fetch when dropped.
+    struct Task(Rc<std::cell::Cell<bool>>);
+    impl Drop for Task {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+    let state = |url: &str| FeedState {
+        url: url.to_string(),
+        blogs: None,
+        cached_at: None,
+        error: None,
+    };
+    let (zh, en) = ("https://example.com/zh.xml".to_string(), "https://example.com/en.xml".to_string());
+    let (zh_aborted, en_aborted) = (Rc::new(std::cell::Cell::new(false)), Rc::new(std::cell::Cell::new(false)));
+    let mut feeds = Feeds::<u32, Task>::new();
+    feeds.insert(state(&zh), Some(Task(zh_aborted.clone())));
+    feeds.insert(state(&en), Some(Task(en_aborted.clone())));
+    assert!(feeds.watch(1, vec![zh.clone()]).contains(&en));
+    assert!(en_aborted.get());
+    assert!(feeds.watch(2, vec![zh.clone()]).is_empty());
+
+    // Tile 1 switches language; tile 2 still watches the old feed until it
+    // unmounts, and only then is that fetch aborted.
+    assert!(feeds.watch(1, vec![en.clone()]).is_empty());
+    assert!(!zh_aborted.get());
+    assert_eq!(feeds.unwatch(2), vec![zh.clone()]);
+    assert!(zh_aborted.get());
+
+    // A response that still arrives for it is kept but reaches nobody.
+    let blogs = Blogs {
+        last_update: crate::util::epoch(),
+        blogs: vec![],
+        warnings: vec![],
+        meta: None,
+    };
+    assert!(feeds.complete(&zh, Ok(blogs)));
+    assert!(feeds.watchers_of(&zh).is_empty());
+    assert_eq!(feeds.watchers_of(&en), vec![1]);
+    assert!(!feeds.complete("https://example.com/unknown.xml", Err(FetchError::Timeout)));
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// Site configuration read from `sources.json` next to `index.html`. It is
+/// embedded at compile time, so a fork only has to edit that file and rebuild.
+#[derive(Deserialize, Debug, Default)]
+pub struct SiteConfig {
+    /// Feeds to show per language, keyed by `Language::lang`.
+    #[serde(default)]
+    pub blogs: BTreeMap<String, Vec<FeedSource>>,
+    /// Repositories for the projects page. Without it only the snapshot in
+    /// `projects.json` is shown.
+    #[serde(default)]
+    pub projects: Option<ProjectSource>,
+    /// Feeds of notes and reading lists for the learning page, keyed like
+    /// `blogs`. Entries are grouped and annotated through their tags, see
+    /// `learning_tile::classify`.
+    #[serde(default)]
+    pub learning: BTreeMap<String, Vec<FeedSource>>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FeedSource {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub badge: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectSource {
+    /// A GitHub REST API repository listing, e.g.
+    /// `https://api.github.com/users/{user}/repos`.
+    pub url: String,
+    /// Repository names listed first, in this order.
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// Also list forks and archived repositories.
+    #[serde(default)]
+    pub show_all: bool,
+}
+
+impl SiteConfig {
+    pub fn parse(data: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(data)
+    }
+
+    pub fn blog_sources(&self, lang: &str) -> &[FeedSource] {
+        self.blogs.get(lang).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn learning_sources(&self, lang: &str) -> &[FeedSource] {
+        self.learning.get(lang).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+pub fn site() -> &'static SiteConfig {
+    static SITE: OnceLock<SiteConfig> = OnceLock::new();
+    SITE.get_or_init(|| match SiteConfig::parse(include_str!("../sources.json")) {
+        Ok(config) => config,
+        Err(err) => {
+            yew::services::ConsoleService::error(&format!("invalid sources.json: {}", err));
+            SiteConfig::default()
+        }
+    })
+}
+
+#[test]
+fn test_embedded_config() {
+    let config = SiteConfig::parse(include_str!("../sources.json")).unwrap();
+    assert!(!config.blog_sources("zh").is_empty());
+    assert!(!config.blog_sources("en").is_empty());
+    assert!(config.blog_sources("fr").is_empty());
+    assert!(config.projects.is_some());
+    assert!(!config.learning_sources("zh").is_empty());
+    assert!(!config.learning_sources("en").is_empty());
+    assert!(config.learning_sources("fr").is_empty());
+}
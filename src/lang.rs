@@ -1,59 +1,223 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use chrono::Duration;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+
+/// Message catalogs in Fluent syntax, one per `locales/<lang>.ftl` and
+/// sorted by language; `build.rs` lists them, so adding a language only
+/// takes adding its file.
+const CATALOGS: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/catalogs.rs"));
+
+/// Catalog that fills in messages a language has not translated (yet).
+pub const FALLBACK: &str = "en";
+
+/// Language shown when neither the URL, a saved choice nor the browser
+/// asks for a supported one.
+const DEFAULT: &str = "zh";
+
+type Bundle = FluentBundle<FluentResource>;
+
+thread_local! {
+    static BUNDLES: RefCell<HashMap<&'static str, Rc<Bundle>>> = RefCell::new(HashMap::new());
+}
+
+/// UI text for one language, looked up by message id in its catalog.
+/// Cheap to clone: catalogs are parsed once and shared.
+#[derive(Clone)]
 pub struct Language {
     pub lang: &'static str,
-    pub nav_lang_link: &'static str,
-    pub nav_blogs: &'static str,
-    pub nav_projects: &'static str,
-    pub nav_learning: &'static str,
-
-    pub last_update: &'static str,
-    pub time_week: &'static str,
-    pub time_day: &'static str,
-    pub time_hour: &'static str,
-    pub time_minutes: &'static str,
-    pub time_ago:&'static str,
-    pub time_just: &'static str,
+    bundle: Rc<Bundle>,
+    fallback: Rc<Bundle>,
 }
 
 impl Language {
-    pub fn from_lang(lang:&'static str) -> Self {
-        match lang {
-            "zh" => Self::zh(),
-            "en" => Self::en(),
-            _ => panic!("unknown lang")
+    /// Unsupported languages get the fallback catalog instead of failing.
+    pub fn from_lang(lang: &str) -> Self {
+        let lang = supported(lang).unwrap_or(FALLBACK);
+        Self {
+            lang,
+            bundle: bundle(lang),
+            fallback: bundle(FALLBACK),
         }
     }
 
-    pub fn zh() -> Self {
-        Self {
-            lang: "zh",
-            nav_lang_link: "语言",
-            nav_blogs: "文章",
-            nav_projects: "项目",
-            nav_learning: "学习",
-            time_week: "周",
-            time_day: "天",
-            time_hour: "小时",
-            time_ago: "前",
-            time_minutes: "分钟",
-            time_just: "刚刚",
-            last_update: "最后更新",
-        }
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, None)
     }
 
-    pub fn en() -> Self {
-        Self {
-            lang: "en",
-            nav_lang_link: "Language",
-            nav_blogs: "Blogs",
-            nav_projects: "Projects",
-            nav_learning: "Learning",
-            time_week: "weeks",
-            time_day: "days",
-            time_hour: "hours",
-            time_ago: "ago",
-            time_minutes: "minutes",
-            time_just: "just now",
-            last_update:"Last update"
-        }
+    /// Formats message `id`, falling back to the `FALLBACK` catalog. A
+    /// message missing from both renders as its id, so a gap shows up on the
+    /// page instead of breaking it.
+    pub fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        [&self.bundle, &self.fallback]
+            .iter()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = vec![];
+                Some(bundle.format_pattern(pattern, args, &mut errors).into_owned())
+            })
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Describes how long ago `elapsed` was, e.g. "3 days ago", or for a
+    /// negative duration (future dates, clock skew) "in 3 days". Anything
+    /// under a minute either way is "just now".
+    pub fn relative_time(&self, elapsed: Duration) -> String {
+        let future = elapsed < Duration::zero();
+        let minutes = elapsed.num_minutes().saturating_abs();
+        let days = elapsed.num_days().saturating_abs();
+        let (unit, count) = if minutes == 0 {
+            return self.text("time-now");
+        } else if minutes < 60 {
+            ("minute", minutes)
+        } else if minutes < 24 * 60 {
+            ("hour", minutes / 60)
+        } else if days < 7 {
+            ("day", days)
+        } else if days < 30 {
+            ("week", days / 7)
+        } else if days < 365 {
+            ("month", days / 30)
+        } else {
+            ("year", days / 365)
+        };
+        let id = format!("time-{}-{}", if future { "future" } else { "past" }, unit);
+        let mut args = FluentArgs::new();
+        args.set("count", count);
+        self.format(&id, Some(&args))
+    }
+}
+
+/// Languages with a catalog.
+pub fn languages() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(lang, _)| *lang)
+}
+
+fn bundle(lang: &'static str) -> Rc<Bundle> {
+    BUNDLES.with(|bundles| {
+        bundles
+            .borrow_mut()
+            .entry(lang)
+            .or_insert_with(|| Rc::new(load_bundle(lang)))
+            .clone()
+    })
+}
+
+/// Builds the bundle for `lang`. Syntax errors only drop the broken
+/// entries, the rest of the catalog stays usable.
+fn load_bundle(lang: &'static str) -> Bundle {
+    let source = CATALOGS
+        .iter()
+        .find(|(name, _)| *name == lang)
+        .map(|(_, source)| *source)
+        .unwrap_or_default();
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+        yew::services::ConsoleService::error(&format!("invalid catalog {}.ftl: {:?}", lang, errors));
+        resource
+    });
+    let mut bundle = Bundle::new(vec![lang.parse().unwrap_or_default()]);
+    // Bidi isolation marks around arguments would end up in attributes.
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        yew::services::ConsoleService::error(&format!("duplicate messages in {}.ftl: {:?}", lang, errors));
     }
-}
\ No newline at end of file
+    bundle
+}
+
+const STORAGE_KEY: &str = "io-index.lang";
+
+/// Maps a BCP 47 tag such as `zh-CN` or `en` to a supported language.
+pub fn supported(tag: &str) -> Option<&'static str> {
+    let primary = tag.trim().split(&['-', '_'][..]).next()?.to_lowercase();
+    languages().find(|lang| *lang == primary)
+}
+
+/// Picks the first supported language from, in order, a `?lang=` URL
+/// parameter, the choice saved by `save_lang`, and `navigator.languages`.
+pub fn initial_lang() -> &'static str {
+    let window = web_sys::window();
+    let search = window.as_ref().and_then(|w| w.location().search().ok()).unwrap_or_default();
+    let from_url = url::form_urlencoded::parse(search.trim_start_matches('?').as_bytes())
+        .find(|(key, _)| key == "lang")
+        .map(|(_, value)| value.into_owned())
+        .or_else(|| crate::route::query_param("lang"));
+    let saved = yew::services::StorageService::new(yew::services::storage::Area::Local)
+        .ok()
+        .and_then(|storage| storage.restore::<Result<String, anyhow::Error>>(STORAGE_KEY).ok());
+    let browser = window
+        .map(|w| w.navigator().languages().iter().filter_map(|lang| lang.as_string()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    from_url
+        .into_iter()
+        .chain(saved)
+        .chain(browser)
+        .find_map(|tag| supported(&tag))
+        .or_else(|| supported(DEFAULT))
+        .unwrap_or(FALLBACK)
+}
+
+/// Remembers the choice for the next visit and updates `<html lang>`.
+pub fn save_lang(lang: &str) {
+    if let Ok(mut storage) = yew::services::StorageService::new(yew::services::storage::Area::Local) {
+        storage.store(STORAGE_KEY, Ok(lang.to_string()));
+    }
+    apply_lang(lang);
+}
+
+pub fn apply_lang(lang: &str) {
+    let root = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.document_element());
+    if let Some(root) = root {
+        let _ = root.set_attribute("lang", lang);
+    }
+}
+
+#[test]
+fn test_supported() {
+    assert_eq!(supported("zh-CN"), Some("zh"));
+    assert_eq!(supported("zh_Hant_TW"), Some("zh"));
+    assert_eq!(supported("EN-us"), Some("en"));
+    assert_eq!(supported("ja"), None);
+    assert_eq!(supported(""), None);
+}
+
+#[test]
+fn test_catalogs() {
+    let fallback = Language::from_lang(FALLBACK);
+    assert_eq!(supported(DEFAULT), Some(DEFAULT));
+    assert_eq!(supported(FALLBACK), Some(FALLBACK));
+    for lang in languages() {
+        let source = CATALOGS.iter().find(|(name, _)| *name == lang).unwrap().1;
+        assert!(FluentResource::try_new(source.to_string()).is_ok(), "{}.ftl does not parse", lang);
+        let language = Language::from_lang(lang);
+        assert_eq!(language.lang, lang);
+        assert!(language.bundle.has_message("lang-name"), "{}.ftl has no lang-name", lang);
+    }
+    assert_eq!(Language::from_lang("zh-CN").text("nav-blogs"), "文章");
+    assert_eq!(fallback.text("nav-blogs"), "Blogs");
+    // Unknown languages and missing messages degrade instead of panicking.
+    assert_eq!(Language::from_lang("xx").lang, FALLBACK);
+    assert_eq!(fallback.text("no-such-message"), "no-such-message");
+}
+
+#[test]
+fn test_relative_time() {
+    let en = Language::from_lang("en");
+    let zh = Language::from_lang("zh");
+    assert_eq!(en.relative_time(Duration::seconds(30)), "just now");
+    assert_eq!(en.relative_time(Duration::seconds(-30)), "just now");
+    assert_eq!(en.relative_time(Duration::minutes(1)), "1 minute ago");
+    assert_eq!(en.relative_time(Duration::minutes(5)), "5 minutes ago");
+    assert_eq!(en.relative_time(Duration::hours(23)), "23 hours ago");
+    assert_eq!(en.relative_time(Duration::days(1)), "1 day ago");
+    assert_eq!(en.relative_time(Duration::days(-3)), "in 3 days");
+    assert_eq!(en.relative_time(Duration::days(14)), "2 weeks ago");
+    assert_eq!(en.relative_time(Duration::days(45)), "1 month ago");
+    assert_eq!(en.relative_time(Duration::days(-800)), "in 2 years");
+    assert_eq!(zh.relative_time(Duration::weeks(1)), "1 周前");
+    assert_eq!(zh.relative_time(Duration::hours(-2)), "2 小时后");
+    // Extremes must not overflow.
+    assert!(en.relative_time(Duration::MAX).ends_with("years ago"));
+    assert!(en.relative_time(Duration::MIN).starts_with("in "));
+}
@@ -0,0 +1,306 @@
+use yew::{prelude::*, Properties};
+
+use crate::blog_tile::slots::{FeedSlots, FeedStatus};
+use crate::blog_tile::store::FeedState;
+use crate::blog_tile::{render_retry, Blog};
+use crate::lang::Language;
+use crate::route::AppRoute;
+
+/// Tag prefixes that annotate learning entries rather than label them,
+/// e.g. `<category>topic:Rust</category>`.
+const TOPIC: &str = "topic:";
+const PROGRESS: &str = "progress:";
+const KIND: &str = "kind:";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    /// A note written here; opens in the reader.
+    Note,
+    /// Something being read elsewhere: a book, course or series. Tagged
+    /// `kind:reading`.
+    Reading,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningItem<'a> {
+    pub blog: &'a Blog,
+    pub kind: ItemKind,
+    /// Percent done, from a `progress:60`, `progress:60%` or `progress:3/5`
+    /// tag.
+    pub progress: Option<u8>,
+    /// Tags without annotations and without the one used as topic.
+    pub tags: Vec<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic<'a> {
+    /// `None` for entries without any tag.
+    pub name: Option<&'a str>,
+    pub items: Vec<LearningItem<'a>>,
+}
+
+impl Topic<'_> {
+    /// Average progress of the entries that track it.
+    pub fn progress(&self) -> Option<u8> {
+        let tracked = self.items.iter().filter_map(|item| item.progress).collect::<Vec<_>>();
+        if tracked.is_empty() {
+            return None;
+        }
+        let total = tracked.iter().map(|p| u32::from(*p)).sum::<u32>();
+        Some((total / tracked.len() as u32) as u8)
+    }
+}
+
+fn parse_progress(value: &str) -> Option<u8> {
+    let value = value.trim().trim_end_matches('%');
+    let percent = match value.split_once('/') {
+        Some((done, total)) => {
+            let (done, total) = (done.trim().parse::<u32>().ok()?, total.trim().parse::<u32>().ok()?);
+            if total == 0 {
+                return None;
+            }
+            // In `u64`, as `done` comes straight from the feed.
+            u64::from(done) * 100 / u64::from(total)
+        }
+        None => value.parse::<u64>().ok()?,
+    };
+    Some(percent.min(100) as u8)
+}
+
+/// Reads the annotations of one entry. Its topic is the `topic:` tag, or
+/// else its first plain tag.
+pub fn classify(blog: &Blog) -> (Option<&str>, LearningItem<'_>) {
+    let mut topic = None;
+    let mut item = LearningItem {
+        blog,
+        kind: ItemKind::Note,
+        progress: None,
+        tags: Vec::new(),
+    };
+    for tag in &blog.tags {
+        if let Some(name) = tag.strip_prefix(TOPIC) {
+            topic = topic.or(Some(name.trim()));
+        } else if let Some(value) = tag.strip_prefix(PROGRESS) {
+            item.progress = parse_progress(value);
+        } else if let Some(kind) = tag.strip_prefix(KIND) {
+            if kind.trim().eq_ignore_ascii_case("reading") {
+                item.kind = ItemKind::Reading;
+            }
+        } else {
+            item.tags.push(tag);
+        }
+    }
+    if topic.is_none() && !item.tags.is_empty() {
+        topic = Some(item.tags.remove(0));
+    }
+    (topic, item)
+}
+
+/// Entries grouped by topic, topics in alphabetical order with the
+/// untagged ones last; entries keep the order of `blogs`.
+pub fn group(blogs: &[Blog]) -> Vec<Topic<'_>> {
+    let mut topics: Vec<Topic> = Vec::new();
+    for blog in blogs {
+        let (name, item) = classify(blog);
+        let same = |topic: &&mut Topic| match (topic.name, name) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (a, b) => a == b,
+        };
+        match topics.iter_mut().find(same) {
+            Some(topic) => topic.items.push(item),
+            None => topics.push(Topic { name, items: vec![item] }),
+        }
+    }
+    topics.sort_by_key(|topic| (topic.name.is_none(), topic.name.map(str::to_lowercase)));
+    topics
+}
+
+/// Notes and reading lists from the learning feeds of `sources.json`,
+/// grouped by topic with how far along each one is.
+pub struct LearningTile {
+    link: ComponentLink<Self>,
+    language: Language,
+    /// One slot per learning source of `lang`.
+    feeds: FeedSlots,
+    props: LearningProperty,
+}
+
+#[derive(Properties, Clone)]
+pub struct LearningProperty {
+    #[prop_or("zh")]
+    pub lang: &'static str,
+}
+
+pub enum LearningMessage {
+    Feed(FeedState),
+    Retry,
+}
+
+impl Component for LearningTile {
+    type Message = LearningMessage;
+
+    type Properties = LearningProperty;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut tile = Self {
+            link: link.clone(),
+            language: Language::from_lang(props.lang),
+            feeds: FeedSlots::new(link.callback(LearningMessage::Feed)),
+            props,
+        };
+        tile.fetch();
+        tile
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            LearningMessage::Feed(state) => self.feeds.update(state, &self.language),
+            LearningMessage::Retry => {
+                self.feeds.retry(&self.language);
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props.lang == props.lang {
+            return false;
+        }
+        self.props = props;
+        self.language = Language::from_lang(self.props.lang);
+        self.fetch();
+        true
+    }
+
+    fn view(&self) -> Html {
+        let content = match self.feeds.status() {
+            FeedStatus::Fetching => html! {
+                <progress class="progress is-small is-primary" max="100"></progress>
+            },
+            FeedStatus::Done(blogs) if blogs.blogs.is_empty() => html! {
+                <p>{self.language.text("learning-empty")}</p>
+            },
+            FeedStatus::Done(blogs) => html! {
+                <div class="columns is-multiline">
+                { for group(&blogs.blogs).iter().map(|topic| self.render_topic(topic)) }
+                </div>
+            },
+            FeedStatus::Err(err) => {
+                let failed = !self.feeds.source_errors(&self.language).is_empty();
+                html! {
+                    <>
+                        <p>{err}</p>
+                        { if failed { self.render_retry() } else { html! {} } }
+                    </>
+                }
+            }
+        };
+        html! { <div style="padding:10px;">{ content }</div> }
+    }
+}
+
+impl LearningTile {
+    fn fetch(&mut self) {
+        let sources = crate::config::site().learning_sources(self.props.lang);
+        self.feeds.watch(sources.iter().enumerate().collect(), self.language.text("no-learning-source"));
+    }
+
+    fn render_retry(&self) -> Html {
+        render_retry(&self.language, self.link.callback(|_| LearningMessage::Retry))
+    }
+
+    fn render_progress(&self, progress: Option<u8>, classes: &str) -> Html {
+        match progress {
+            Some(percent) => {
+                let mut args = fluent_bundle::FluentArgs::new();
+                args.set("percent", percent);
+                let label = self.language.format("learning-progress", Some(&args));
+                html! {
+                    <progress class=("progress", classes) value=percent.to_string() max="100" title=label.clone()>
+                        {label}
+                    </progress>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    fn render_topic(&self, topic: &Topic) -> Html {
+        let name = topic.name.map_or_else(|| self.language.text("learning-other"), str::to_string);
+        let mut args = fluent_bundle::FluentArgs::new();
+        args.set("count", topic.items.len());
+        html! {
+            <div class="column is-half-tablet is-one-third-desktop">
+            <div class="box" style="height:100%;">
+                <div class="level is-mobile" style="margin-bottom:0.5rem;">
+                    <div class="level-left"><p class="title is-5">{name}</p></div>
+                    <div class="level-right">
+                        <span class="has-text-grey is-size-7">{self.language.format("learning-count", Some(&args))}</span>
+                    </div>
+                </div>
+                { self.render_progress(topic.progress(), "is-success is-small") }
+                <ul>
+                { for topic.items.iter().map(|item| self.render_item(item)) }
+                </ul>
+            </div>
+            </div>
+        }
+    }
+
+    fn render_item(&self, item: &LearningItem) -> Html {
+        let blog = item.blog;
+        let title = if blog.title.is_empty() { self.language.text("empty-title") } else { blog.title.clone() };
+        let (link, kind) = match item.kind {
+            ItemKind::Note => (
+                html! { <a href=AppRoute::Reader(blog.link.clone()).to_hash()>{title}</a> },
+                self.language.text("learning-note"),
+            ),
+            ItemKind::Reading => (
+                html! { <a href=blog.link.as_str() target="_blank" rel="noopener">{title}</a> },
+                self.language.text("learning-reading"),
+            ),
+        };
+        html! {
+            <li style="margin-top:0.75rem;">
+                <p>{link}</p>
+                <div class="tags" style="margin-bottom:0.25rem;">
+                    <span class="tag is-info is-light">{kind}</span>
+                    { for item.tags.iter().map(|tag| html! { <span class="tag">{tag}</span> }) }
+                    <span class="tag is-white has-text-grey">{blog.last_update.format("%Y-%m-%d").to_string()}</span>
+                </div>
+                { self.render_progress(item.progress, "is-info is-small") }
+            </li>
+        }
+    }
+}
+
+#[test]
+fn test_group_learning() {
+    let entry = |title, tags| crate::blog_tile::blog(title, tags, 0);
+    let blogs = vec![
+        entry("ownership", &["Rust", "memory"]),
+        entry("loose", &[]),
+        entry("book", &["topic:rust", "kind:reading", "progress:3/4", "book"]),
+        entry("tcp", &["Networking", "progress:40%"]),
+        entry("async", &["topic:Rust", "progress:50"]),
+    ];
+    let topics = group(&blogs);
+    let names = topics.iter().map(|topic| topic.name).collect::<Vec<_>>();
+    assert_eq!(names, vec![Some("Networking"), Some("Rust"), None]);
+
+    let rust = &topics[1];
+    let titles = rust.items.iter().map(|item| item.blog.title.as_str()).collect::<Vec<_>>();
+    assert_eq!(titles, vec!["ownership", "book", "async"]);
+    assert_eq!(rust.items[0].tags, vec!["memory"]);
+    assert_eq!(rust.items[1].kind, ItemKind::Reading);
+    assert_eq!(rust.items[1].progress, Some(75));
+    assert_eq!(rust.items[1].tags, vec!["book"]);
+    assert_eq!(rust.progress(), Some(62));
+    assert_eq!(topics[2].progress(), None);
+
+    assert_eq!(parse_progress("120"), Some(100));
+    assert_eq!(parse_progress("1/0"), None);
+    assert_eq!(parse_progress("soon"), None);
+    assert_eq!(parse_progress("50000000/1"), Some(100));
+    assert_eq!(parse_progress("4294967295/4294967295"), Some(100));
+}
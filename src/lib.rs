@@ -1,53 +1,100 @@
-#![recursion_limit="256"]
+#![recursion_limit="512"]
 
 use lang::Language;
+use route::{AppRoute, RouteListener};
 use wasm_bindgen::prelude::*;
 use yew::prelude::*;
-use ybc::NavbarItemTag::{ A, Div };
-use ybc::TileCtx::{Ancestor, Child, Parent};
-use ybc::NavbarFixed::Top;
-use ybc::TileSize::Four;
-use yewtil::future::LinkFuture;
+use ybc::NavbarItemTag::{A, Div};
+use ybc::TileCtx::{Ancestor, Parent};
+use about_tile::AboutTile;
 use blog_tile::BlogTile;
+use learning_tile::LearningTile;
+use project_tile::ProjectTile;
+use reader::Reader;
+use search::SearchBox;
+use theme::{SystemThemeListener, Theme, ThemeChoice};
 
 mod lang;
+mod about_tile;
 mod blog_tile;
+mod config;
+mod learning_tile;
+mod project_tile;
+mod reader;
+mod route;
+mod search;
+mod theme;
 mod util;
 
 struct Model {
     link: ComponentLink<Self>,
-    value: i64,
-    language: Language
+    language: Language,
+    route: AppRoute,
+    theme_choice: ThemeChoice,
+    /// Whether the system prefers a dark theme, for `ThemeChoice::System`.
+    system_dark: bool,
+    _route_listener: RouteListener,
+    _theme_listener: SystemThemeListener,
 }
 
 enum Msg {
-    AddOne,
-    ChangeLangEn,
-    ChangeLangZh
+    ChangeLang(&'static str),
+    RouteChanged(AppRoute),
+    ChangeTheme(ThemeChoice),
+    SystemThemeChanged(bool),
 }
 
 impl Component for Model {
     type Message = Msg;
     type Properties = ();
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        yew::services::ConsoleService::log("new");
+        let route_listener = RouteListener::new(link.callback(Msg::RouteChanged));
+        let lang = lang::initial_lang();
+        lang::apply_lang(lang);
+        let theme_choice = theme::initial_choice();
+        let system_dark = theme::system_dark();
+        theme::apply(theme_choice.resolve(system_dark));
         Self {
+            _theme_listener: SystemThemeListener::new(link.callback(Msg::SystemThemeChanged)),
             link,
-            value: 0,
-            language: Language::zh()
+            language: Language::from_lang(lang),
+            route: AppRoute::current(),
+            theme_choice,
+            system_dark,
+            _route_listener: route_listener,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::AddOne => self.value += 1,
-            Msg::ChangeLangEn => {
-                yew::services::ConsoleService::log("change lang to en");
-                self.language = Language::en()
+            Msg::RouteChanged(route) => {
+                if self.route == route {
+                    return false;
+                }
+                self.route = route;
             }
-            Msg::ChangeLangZh => {
-                yew::services::ConsoleService::log("change lang to zh");
-                self.language = Language::zh()
+            Msg::ChangeLang(lang) => {
+                if self.language.lang == lang {
+                    return false;
+                }
+                self.language = Language::from_lang(lang);
+                lang::save_lang(self.language.lang);
+            }
+            Msg::ChangeTheme(choice) => {
+                if self.theme_choice == choice {
+                    return false;
+                }
+                self.theme_choice = choice;
+                theme::save_choice(choice);
+                theme::apply(self.theme());
+            }
+            Msg::SystemThemeChanged(dark) => {
+                let before = self.theme();
+                self.system_dark = dark;
+                if self.theme() == before {
+                    return false;
+                }
+                theme::apply(self.theme());
             }
         }
         true
@@ -63,19 +110,9 @@ impl Component for Model {
     fn view(&self) -> Html {
         html! {
             <div>
-                <ybc::Navbar spaced=true padded=true transparent=true navburger=true navbrand=self.view_navbrand() navstart=self.view_navstart() navend=self.view_lang_drop() classes="is-dark" />
-                // <ybc::Button onclick=self.link.callback(|x| Msg::AddOne)>{ "+1" }</ybc::Button>
-                <section class="hero is-primary">
-                <div class="hero-body">
-                    <div class="container">
-                    <h1 class="title">
-                        {"建设中... Constructing..."}
-                    </h1>
-                    </div>
-                </div>
-                </section>
-                { self.view_blogs() }
-                <footer class="footer">
+                <ybc::Navbar spaced=true padded=true transparent=true navburger=true navbrand=self.view_navbrand() navstart=self.view_navstart() navend=self.view_navend() classes=self.theme().navbar() />
+                { self.view_page() }
+                <footer class=("footer", self.theme().footer())>
                     <div class="content has-text-centered">
                         <p>
                         {"The source code is licensed "}
@@ -90,10 +127,14 @@ impl Component for Model {
 }
 
 impl Model {
+    fn theme(&self) -> Theme {
+        self.theme_choice.resolve(self.system_dark)
+    }
+
     fn view_navbrand(&self) -> Html {
         html!{
             <>
-                <ybc::NavbarItem tag=A>
+                <ybc::NavbarItem tag=A href=AppRoute::Home.to_hash()>
                     <img src="https://bulma.io/images/bulma-logo.png" />
                 </ybc::NavbarItem>
             </>
@@ -102,84 +143,146 @@ impl Model {
 
     fn view_lang_link(&self) -> Html {
         html! {
-            { self.language.nav_lang_link }
+            { self.language.text("nav-lang-link") }
+        }
+    }
+
+    fn view_navend(&self) -> Html {
+        html! {
+            <>
+                <ybc::NavbarItem tag=Div>
+                    <SearchBox lang={self.language.lang} />
+                </ybc::NavbarItem>
+                { self.view_lang_drop() }
+                { self.view_theme_drop() }
+            </>
         }
     }
 
     fn view_lang_drop(&self) -> Html {
         html! {
             <ybc::NavbarDropdown navlink=self.view_lang_link() hoverable=true>
-                <ybc::NavbarItem tag=A href="javascript:void(0)">
-                    <div onclick=self.link.callback(|_|Msg::ChangeLangZh)>
-                        { "中文" }
-                    </div>
-                </ybc::NavbarItem>
-                <ybc::NavbarItem tag=A href="javascript:void(0)">
-                    <div onclick=self.link.callback(|_|Msg::ChangeLangEn)>
-                        { "English" }
-                    </div>
-                </ybc::NavbarItem>
+                { for lang::languages().map(|lang| html! {
+                    <ybc::NavbarItem tag=A href="javascript:void(0)">
+                        <div onclick=self.link.callback(move |_| Msg::ChangeLang(lang))>
+                            { Language::from_lang(lang).text("lang-name") }
+                        </div>
+                    </ybc::NavbarItem>
+                }) }
             </ybc::NavbarDropdown>
         }
     }
 
-    fn view_navdrop(&self) -> Html {
+    fn view_theme_drop(&self) -> Html {
+        let navlink = html! { { self.language.text(self.theme_choice.label()) } };
         html! {
-            <ybc::NavbarDropdown navlink=self.view_navlink() hoverable=true>
-                <ybc::NavbarItem tag=A>
-                    { "About" }
-                </ybc::NavbarItem>
-                <ybc::NavbarItem tag=A>
-                    { "Jobs" }
-                </ybc::NavbarItem>
-                <ybc::NavbarItem tag=A>
-                    { "Contact" }
-                </ybc::NavbarItem>
-                <ybc::NavbarDivider />
-                <ybc::NavbarItem tag=A>
-                    { "Report an issue" }
-                </ybc::NavbarItem>
+            <ybc::NavbarDropdown navlink=navlink hoverable=true>
+                { for ThemeChoice::ALL.iter().map(|&choice| html! {
+                    <ybc::NavbarItem tag=A href="javascript:void(0)" active={choice == self.theme_choice}>
+                        <div onclick=self.link.callback(move |_| Msg::ChangeTheme(choice))>
+                            { self.language.text(choice.label()) }
+                        </div>
+                    </ybc::NavbarItem>
+                }) }
             </ybc::NavbarDropdown>
         }
     }
 
-    fn view_navend(&self) -> Html {
+    // Contruct the contents of the `navbar-start` section and return Html type that navstart property of Navbar expects.
+    // Html type gets tossed into navstart field of NavbarProps struct. Consult ybc Docs for more info.
+    fn view_navstart(&self) -> Html {
         html! {
-            <ybc::NavbarItem tag=Div>
-                // Create div container for button groups
-                <ybc::Buttons>
-                    // Button classes property accepts Option<String> type. `is-primary` here provides color styling. 
-                    <ybc::Button classes=Some("is-primary")>
-                        <strong>{ "Sign up" }</strong>
-                    </ybc::Button>
-                    <ybc::Button classes=Some("is-light")>
-                        { "Log in" }
-                    </ybc::Button>
-                </ybc::Buttons>
-            </ybc::NavbarItem>
+            <>
+                { self.view_nav_item(AppRoute::Blogs, self.language.text("nav-blogs")) }
+                { self.view_nav_item(AppRoute::Projects, self.language.text("nav-projects")) }
+                { self.view_nav_item(AppRoute::Learning, self.language.text("nav-learning")) }
+                { self.view_nav_item(AppRoute::About, self.language.text("nav-about")) }
+            </>
         }
     }
 
-    // Contruct the contents of the `navbar-link` section and return Html type that navlink property of NavbarDropdown expects.
-    // Html type gets tossed into navlink field of NavbarDropdownProps struct. Consult ybc Docs for more info.
-    fn view_navlink(&self) -> Html {
+    fn view_nav_item(&self, route: AppRoute, text: String) -> Html {
         html! {
-            { "More" }
+            <ybc::NavbarItem tag=A tab=true active={self.route == route} href=route.to_hash()>
+                { text }
+            </ybc::NavbarItem>
         }
     }
 
-    // Contruct the contents of the `navbar-start` section and return Html type that navstart property of Navbar expects.
-    // Html type gets tossed into navstart field of NavbarProps struct. Consult ybc Docs for more info.
-    fn view_navstart(&self) -> Html {
+    fn view_page(&self) -> Html {
+        match &self.route {
+            AppRoute::Home => html! {
+                <>
+                    { self.view_hero(&self.language.text("constructing")) }
+                    { self.view_blogs() }
+                </>
+            },
+            AppRoute::Blogs => html! {
+                <>
+                    { self.view_hero(&self.language.text("nav-blogs")) }
+                    <ybc::Container fluid=true>
+                        <div class=("box", self.theme().tile()) style="padding:0px;">
+                            <BlogTile lang={self.language.lang} merged=true page_size=10 controls=true url_state=true poll_secs=300 />
+                        </div>
+                    </ybc::Container>
+                </>
+            },
+            AppRoute::Projects => html! {
+                <>
+                    { self.view_hero(&self.language.text("nav-projects")) }
+                    <ybc::Container fluid=true>
+                        <div class=("box", self.theme().tile()) style="padding:0px;">
+                            <ProjectTile lang={self.language.lang} controls=true />
+                        </div>
+                    </ybc::Container>
+                </>
+            },
+            AppRoute::Learning => html! {
+                <>
+                    { self.view_hero(&self.language.text("nav-learning")) }
+                    <ybc::Container fluid=true>
+                        <LearningTile lang={self.language.lang} />
+                    </ybc::Container>
+                </>
+            },
+            AppRoute::About => html! {
+                <>
+                    { self.view_hero(&self.language.text("nav-about")) }
+                    <ybc::Container fluid=true>
+                        <div class=("box", self.theme().tile())>
+                            <AboutTile lang={self.language.lang} />
+                        </div>
+                    </ybc::Container>
+                </>
+            },
+            AppRoute::Reader(url) => html! {
+                <Reader lang={self.language.lang} url=url.clone() />
+            },
+            AppRoute::NotFound(path) => html! {
+                <section class=("hero is-medium", self.theme().hero())>
+                <div class="hero-body">
+                    <div class="container">
+                    <h1 class="title">{"404"}</h1>
+                    <h2 class="subtitle">{self.language.text("not-found")}{": "}<code>{path}</code></h2>
+                    <a class="button is-light" href=AppRoute::Home.to_hash()>{self.language.text("back-home")}</a>
+                    </div>
+                </div>
+                </section>
+            },
+        }
+    }
+
+    fn view_hero(&self, title: &str) -> Html {
         html! {
-            <>
-                <ybc::NavbarItem tag=A>
-                    { self.language.nav_blogs }
-                </ybc::NavbarItem>
-                <ybc::NavbarItem tag=A>
-                    { self.language.nav_projects }
-                </ybc::NavbarItem>
-            </>
+            <section class=("hero", self.theme().hero())>
+            <div class="hero-body">
+                <div class="container">
+                <h1 class="title">
+                    {title}
+                </h1>
+                </div>
+            </div>
+            </section>
         }
     }
 
@@ -188,21 +291,20 @@ impl Model {
             <>
             <ybc::Container fluid=true>
             <ybc::Tile ctx=Ancestor vertical=true>
-              <ybc::Tile>
               <ybc::Tile ctx=Parent>
-                <div class="tile is-child box" style="padding:0px;">
-                  <BlogTile lang={self.language.lang} />
-                </div>
-                </ybc::Tile>
-                <ybc::Tile ctx=Parent>
-                <div class="tile is-child box" style="padding:0px;">
-                    <BlogTile lang={self.language.lang} />
+                <div class=("tile is-child box", self.theme().tile()) style="padding:0px;">
+                  <BlogTile lang={self.language.lang} merged=true page_size=4 load_more=true poll_secs=300 featured=true
+                    hero_classes=self.theme().hero() />
                 </div>
               </ybc::Tile>
+            <ybc::Tile ctx=Parent>
+                <div class=("tile is-child box", self.theme().tile()) style="padding:0px;">
+                  <ProjectTile lang={self.language.lang} limit=3 />
+                </div>
             </ybc::Tile>
             <ybc::Tile ctx=Parent>
-                <div class="tile is-child box" style="padding:0px;">
-                    <p>{"About"}</p>
+                <div class=("tile is-child box", self.theme().tile()) style="padding:0px;">
+                    <AboutTile lang={self.language.lang} compact=true />
                 </div>
             </ybc::Tile>
             </ybc::Tile>
@@ -0,0 +1,387 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use yew::{prelude::*, ChangeData, Properties};
+
+use crate::blog_tile::cache;
+use crate::blog_tile::request::{fetch_json, RetryTask};
+use crate::blog_tile::{render_retry, FetchError};
+use crate::config::ProjectSource;
+use crate::lang::Language;
+
+/// Shown when the API cannot be reached, e.g. once the unauthenticated rate
+/// limit is used up. For now a hand-written placeholder naming the
+/// repositories this site is published from, without descriptions, languages,
+/// stars or push dates, so its cards stay in file order. Replace it with a
+/// real snapshot from
+/// `curl 'https://api.github.com/users/another-s347/repos?per_page=100' > projects.json`.
+const SNAPSHOT: &str = include_str!("../projects.json");
+
+/// How long a listing cached in `localStorage` is used without asking the
+/// API again. Unauthenticated clients get 60 requests an hour.
+const CACHE_TTL_MINUTES: i64 = 60;
+
+fn is_fresh(fetched_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    let age = now.signed_duration_since(fetched_at);
+    age >= chrono::Duration::zero() && age < chrono::Duration::minutes(CACHE_TTL_MINUTES)
+}
+
+/// The fields we use of a repository in the GitHub REST API.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Repo {
+    pub name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub stargazers_count: u32,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RepoSort {
+    #[default]
+    Pushed,
+    Stars,
+    Name,
+}
+
+impl RepoSort {
+    pub const ALL: [RepoSort; 3] = [RepoSort::Pushed, RepoSort::Stars, RepoSort::Name];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RepoSort::Pushed => "pushed",
+            RepoSort::Stars => "stars",
+            RepoSort::Name => "name",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|sort| sort.as_str() == value)
+    }
+}
+
+/// Pinned repositories first in the configured order, then the rest in
+/// `sort` order. Forks and archived repositories are left out unless
+/// `show_all`.
+pub fn arrange<'a>(repos: &'a [Repo], pinned: &[String], sort: RepoSort, show_all: bool) -> Vec<&'a Repo> {
+    let pin = |repo: &Repo| pinned.iter().position(|name| name.eq_ignore_ascii_case(&repo.name));
+    let mut shown = repos
+        .iter()
+        .filter(|repo| show_all || pin(repo).is_some() || !(repo.fork || repo.archived))
+        .collect::<Vec<_>>();
+    shown.sort_by(|a, b| {
+        let order = match sort {
+            RepoSort::Pushed => b.pushed_at.cmp(&a.pushed_at),
+            RepoSort::Stars => b.stargazers_count.cmp(&a.stargazers_count),
+            RepoSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        };
+        // `None` sorts after any pin.
+        let pins = match (pin(a), pin(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        };
+        pins.then(order)
+    });
+    shown
+}
+
+pub struct ProjectTile {
+    link: ComponentLink<Self>,
+    language: Language,
+    status: ProjectStatus,
+    sort: RepoSort,
+    fetch_task: Option<RetryTask<Vec<Repo>>>,
+    props: ProjectProperty,
+}
+
+#[derive(Properties, Clone)]
+pub struct ProjectProperty {
+    #[prop_or("zh")]
+    pub lang: &'static str,
+    /// Cards to show; `0` shows every repository.
+    #[prop_or_default]
+    pub limit: usize,
+    /// Show the sort control above the cards.
+    #[prop_or_default]
+    pub controls: bool,
+}
+
+pub enum ProjectMessage {
+    Done(Vec<Repo>),
+    Error(FetchError),
+    Sort(RepoSort),
+    Retry,
+}
+
+enum ProjectStatus {
+    Fetching,
+    Done(Vec<Repo>),
+    /// The API failed and an expired cached listing, or else the bundled
+    /// snapshot, is shown instead.
+    Snapshot(Vec<Repo>, FetchError),
+    Err(String),
+}
+
+impl Component for ProjectTile {
+    type Message = ProjectMessage;
+
+    type Properties = ProjectProperty;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut tile = Self {
+            link,
+            language: Language::from_lang(props.lang),
+            status: ProjectStatus::Fetching,
+            sort: RepoSort::default(),
+            fetch_task: None,
+            props,
+        };
+        tile.fetch();
+        tile
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            ProjectMessage::Done(repos) => {
+                self.fetch_task = None;
+                if let Some(source) = self.source() {
+                    cache::store_json(&source.url, &repos);
+                }
+                self.status = ProjectStatus::Done(repos);
+            }
+            ProjectMessage::Error(err) => {
+                self.fetch_task = None;
+                let cached = self
+                    .source()
+                    .and_then(|source| cache::load_json::<Vec<Repo>>(&source.url))
+                    .map(|cached| cached.value);
+                let fallback = cached.or_else(|| serde_json::from_str::<Vec<Repo>>(SNAPSHOT).ok());
+                self.status = match fallback {
+                    Some(repos) if !repos.is_empty() => ProjectStatus::Snapshot(repos, err),
+                    _ => ProjectStatus::Err(err.describe(&self.language)),
+                };
+            }
+            ProjectMessage::Sort(sort) => {
+                if self.sort == sort {
+                    return false;
+                }
+                self.sort = sort;
+            }
+            ProjectMessage::Retry => self.fetch(),
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = self.props.lang != props.lang
+            || self.props.limit != props.limit
+            || self.props.controls != props.controls;
+        if self.props.lang != props.lang {
+            self.language = Language::from_lang(props.lang);
+        }
+        self.props = props;
+        changed
+    }
+
+    fn view(&self) -> Html {
+        let content = match &self.status {
+            ProjectStatus::Fetching => html! {
+                <progress class="progress is-small is-primary" max="100"></progress>
+            },
+            ProjectStatus::Done(repos) => self.render_repos(repos),
+            ProjectStatus::Snapshot(repos, err) => {
+                let mut args = fluent_bundle::FluentArgs::new();
+                args.set("reason", err.describe(&self.language));
+                html! {
+                    <>
+                        <p class="help is-warning" style="padding:0px 10px;">
+                            {self.language.format("project-snapshot", Some(&args))}{" "}
+                            { self.render_retry() }
+                        </p>
+                        { self.render_repos(repos) }
+                    </>
+                }
+            }
+            ProjectStatus::Err(err) => html! {
+                <>
+                    <p>{err}</p>
+                    { self.render_retry() }
+                </>
+            },
+        };
+        html! {
+            <>
+                <div class="level" style="padding:10px;">
+                    <div class="level-left">
+                        <p class="title is-4">{self.language.text("nav-projects")}</p>
+                    </div>
+                    <div class="level-right">{ self.render_controls() }</div>
+                </div>
+                { content }
+            </>
+        }
+    }
+}
+
+impl ProjectTile {
+    fn source(&self) -> Option<&'static ProjectSource> {
+        crate::config::site().projects.as_ref()
+    }
+
+    /// Uses the cached listing while it is younger than `CACHE_TTL_MINUTES`,
+    /// otherwise asks the API.
+    fn fetch(&mut self) {
+        match self.source() {
+            Some(source) => {
+                if let Some(cached) = cache::load_json::<Vec<Repo>>(&source.url) {
+                    if is_fresh(cached.fetched_at, crate::util::now()) {
+                        self.status = ProjectStatus::Done(cached.value);
+                        return;
+                    }
+                }
+                self.status = ProjectStatus::Fetching;
+                self.fetch_task = Some(fetch_json(
+                    &source.url,
+                    self.link.callback(|result| match result {
+                        Ok(repos) => ProjectMessage::Done(repos),
+                        Err(err) => ProjectMessage::Error(err),
+                    }),
+                ));
+            }
+            None => {
+                let repos = serde_json::from_str(SNAPSHOT).unwrap_or_default();
+                self.status = ProjectStatus::Done(repos);
+            }
+        }
+    }
+
+    fn render_controls(&self) -> Html {
+        if !self.props.controls {
+            return html! {};
+        }
+        let lang = &self.language;
+        let on_sort = self.link.callback(|change: ChangeData| {
+            let sort = match change {
+                ChangeData::Select(select) => RepoSort::parse(&select.value()),
+                _ => None,
+            };
+            ProjectMessage::Sort(sort.unwrap_or_default())
+        });
+        let label = |sort: RepoSort| match sort {
+            RepoSort::Pushed => lang.text("project-sort-pushed"),
+            RepoSort::Stars => lang.text("project-sort-stars"),
+            RepoSort::Name => lang.text("project-sort-name"),
+        };
+        html! {
+            <div class="select is-small">
+                <select onchange=on_sort>
+                { for RepoSort::ALL.iter().map(|sort| html! {
+                    <option value=sort.as_str() selected={*sort == self.sort}>{label(*sort)}</option>
+                }) }
+                </select>
+            </div>
+        }
+    }
+
+    fn render_retry(&self) -> Html {
+        render_retry(&self.language, self.link.callback(|_| ProjectMessage::Retry))
+    }
+
+    fn render_repos(&self, repos: &[Repo]) -> Html {
+        let (pinned, show_all) = match self.source() {
+            Some(source) => (source.pinned.as_slice(), source.show_all),
+            None => (&[][..], false),
+        };
+        let mut shown = arrange(repos, pinned, self.sort, show_all);
+        if self.props.limit > 0 {
+            shown.truncate(self.props.limit);
+        }
+        if shown.is_empty() {
+            return html! { <p style="padding:10px;">{self.language.text("project-empty")}</p> };
+        }
+        html! {
+            <div class="columns is-multiline is-gapless">
+            { for shown.into_iter().map(|repo| {
+                let is_pinned = pinned.iter().any(|name| name.eq_ignore_ascii_case(&repo.name));
+                self.render_repo(repo, is_pinned)
+            }) }
+            </div>
+        }
+    }
+
+    fn render_repo(&self, repo: &Repo, pinned: bool) -> Html {
+        let pushed = match repo.pushed_at {
+            Some(pushed_at) => {
+                let mut args = fluent_bundle::FluentArgs::new();
+                args.set("when", self.language.relative_time(crate::util::now() - pushed_at));
+                html! { <span>{self.language.format("project-pushed", Some(&args))}</span> }
+            }
+            None => html! {},
+        };
+        html! {
+            <div class="column is-half-tablet is-one-third-desktop">
+            <ybc::Message>
+                <div class="message-header" style="display:block;">
+                    <h4 class="title is-4 is-spaced">
+                        <a href=repo.html_url.as_str() target="_blank" rel="noopener">{&repo.name}</a>
+                    </h4>
+                    <div class="tags">
+                        { if pinned { html! { <span class="tag is-warning">{self.language.text("project-pinned")}</span> } } else { html! {} } }
+                        { for repo.language.iter().map(|language| html! { <span class="tag is-info">{language}</span> }) }
+                        <span class="tag">{"★ "}{repo.stargazers_count}</span>
+                    </div>
+                </div>
+                <ybc::MessageBody>
+                    { for repo.description.iter().map(|description| html! { <p>{description}</p> }) }
+                    <p class="is-size-7 has-text-grey">{pushed}</p>
+                </ybc::MessageBody>
+            </ybc::Message>
+            </div>
+        }
+    }
+}
+
+#[test]
+fn test_arrange_repos() {
+    let repo = |name: &str, stars, pushed: i64, fork| Repo {
+        name: name.to_string(),
+        html_url: format!("https://github.com/another-s347/{}", name),
+        description: None,
+        language: None,
+        stargazers_count: stars,
+        fork,
+        archived: false,
+        pushed_at: Some(crate::util::from_millis(pushed)),
+    };
+    let repos = vec![
+        repo("alpha", 5, 300, false),
+        repo("beta", 10, 100, false),
+        repo("gamma", 1, 200, false),
+        repo("forked", 50, 400, true),
+    ];
+    let names = |shown: Vec<&Repo>| shown.into_iter().map(|r| r.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(arrange(&repos, &[], RepoSort::Pushed, false)), vec!["alpha", "gamma", "beta"]);
+    assert_eq!(names(arrange(&repos, &[], RepoSort::Stars, true)), vec!["forked", "beta", "alpha", "gamma"]);
+    let pinned = vec!["Gamma".to_string(), "forked".to_string()];
+    assert_eq!(
+        names(arrange(&repos, &pinned, RepoSort::Name, false)),
+        vec!["gamma", "forked", "alpha", "beta"]
+    );
+    let snapshot: Vec<Repo> = serde_json::from_str(SNAPSHOT).unwrap();
+    assert!(!snapshot.is_empty());
+    let roundtrip: Vec<Repo> = serde_json::from_str(&serde_json::to_string(&repos).unwrap()).unwrap();
+    assert_eq!(roundtrip, repos);
+    let now = crate::util::from_millis(1_609_844_430_000);
+    assert!(is_fresh(now - chrono::Duration::minutes(59), now));
+    assert!(!is_fresh(now - chrono::Duration::minutes(61), now));
+    // A cache stamped in the future (clock changed) is not trusted.
+    assert!(!is_fresh(now + chrono::Duration::minutes(5), now));
+}
@@ -0,0 +1,210 @@
+use yew::{prelude::*, virtual_dom::VNode, Properties};
+
+use crate::blog_tile::request::{fetch_document, Document, RetryTask};
+use crate::blog_tile::FetchError;
+use crate::lang::Language;
+use article::Article;
+
+pub mod article;
+pub mod highlight;
+
+/// Shows a post inside the site: fetches its Markdown or HTML source and
+/// renders it with a table of contents. Posts that cannot be fetched, e.g.
+/// because their host does not allow cross-origin requests, get a link to
+/// their own site instead. The reader never navigates there by itself: its
+/// `url` comes from the address bar, so any site could be put there.
+pub struct Reader {
+    link: ComponentLink<Self>,
+    language: Language,
+    status: ReaderStatus,
+    fetch_task: Option<RetryTask<Document>>,
+    props: ReaderProperty,
+}
+
+#[derive(Properties, Clone)]
+pub struct ReaderProperty {
+    #[prop_or("zh")]
+    pub lang: &'static str,
+    /// Address of the post, as linked from its feed.
+    pub url: String,
+}
+
+pub enum ReaderMessage {
+    Done(Document),
+    Error(FetchError),
+    /// Scrolls to the heading with this id.
+    Jump(String),
+}
+
+enum ReaderStatus {
+    Fetching,
+    Done(Article),
+    /// Could not be fetched; the original is linked.
+    Fallback,
+    /// Not a web address the reader may fetch or open.
+    Invalid,
+}
+
+/// Whether `url` is an absolute http(s) address. Anything else, e.g. a
+/// `javascript:` URL in a crafted link, is neither fetched nor opened.
+fn is_web_url(url: &str) -> bool {
+    matches!(url::Url::parse(url), Ok(url) if url.scheme() == "http" || url.scheme() == "https")
+}
+
+impl Component for Reader {
+    type Message = ReaderMessage;
+
+    type Properties = ReaderProperty;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut reader = Self {
+            link,
+            language: Language::from_lang(props.lang),
+            status: ReaderStatus::Fetching,
+            fetch_task: None,
+            props,
+        };
+        reader.fetch();
+        reader
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            ReaderMessage::Done(document) => {
+                self.fetch_task = None;
+                self.status = ReaderStatus::Done(article::render(&document));
+            }
+            ReaderMessage::Error(err) => {
+                self.fetch_task = None;
+                yew::services::ConsoleService::warn(&format!("reader: {}: {}", self.props.url, err));
+                self.status = ReaderStatus::Fallback;
+            }
+            ReaderMessage::Jump(id) => {
+                let heading = web_sys::window()
+                    .and_then(|window| window.document())
+                    .and_then(|document| document.get_element_by_id(&id));
+                if let Some(heading) = heading {
+                    heading.scroll_into_view();
+                }
+                return false;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = self.props.lang != props.lang || self.props.url != props.url;
+        if self.props.lang != props.lang {
+            self.language = Language::from_lang(props.lang);
+        }
+        let refetch = self.props.url != props.url;
+        self.props = props;
+        if refetch {
+            self.fetch();
+        }
+        changed
+    }
+
+    fn view(&self) -> Html {
+        let content = match &self.status {
+            ReaderStatus::Fetching => html! {
+                <progress class="progress is-small is-primary" max="100"></progress>
+            },
+            ReaderStatus::Done(article) => self.render_article(article),
+            ReaderStatus::Fallback => html! {
+                <p>
+                    {self.language.text("reader-fallback")}{" "}
+                    <a href=self.props.url.as_str() target="_blank" rel="noopener">{self.language.text("reader-original")}</a>
+                </p>
+            },
+            ReaderStatus::Invalid => html! { <p>{self.language.text("reader-invalid")}</p> },
+        };
+        html! {
+            <ybc::Section>
+                <ybc::Container>{ content }</ybc::Container>
+            </ybc::Section>
+        }
+    }
+}
+
+impl Reader {
+    fn fetch(&mut self) {
+        if !is_web_url(&self.props.url) {
+            self.fetch_task = None;
+            self.status = ReaderStatus::Invalid;
+            return;
+        }
+        self.status = ReaderStatus::Fetching;
+        self.fetch_task = Some(fetch_document(
+            &self.props.url,
+            self.link.callback(|result| match result {
+                Ok(document) => ReaderMessage::Done(document),
+                Err(err) => ReaderMessage::Error(err),
+            }),
+        ));
+    }
+
+    fn render_article(&self, article: &Article) -> Html {
+        let mut args = fluent_bundle::FluentArgs::new();
+        args.set("minutes", article.minutes);
+        // The sanitized post goes in as markup rather than through `html!`.
+        let body = match yew::utils::document().create_element("div") {
+            Ok(div) => {
+                div.set_class_name("content");
+                div.set_inner_html(&article.html);
+                VNode::VRef(div.into())
+            }
+            Err(_) => html! {},
+        };
+        html! {
+            <div class="columns">
+                <div class="column is-three-quarters-desktop">
+                    { for article.title.iter().map(|title| html! { <h1 class="title is-2">{title}</h1> }) }
+                    <p class="subtitle is-6 has-text-grey">
+                        {self.language.format("reader-minutes", Some(&args))}{" · "}
+                        <a href=self.props.url.as_str() target="_blank" rel="noopener">{self.language.text("reader-original")}</a>
+                    </p>
+                    { body }
+                </div>
+                { self.render_toc(article) }
+            </div>
+        }
+    }
+
+    fn render_toc(&self, article: &Article) -> Html {
+        if article.toc.is_empty() {
+            return html! {};
+        }
+        let top = article.toc.iter().map(|heading| heading.level).min().unwrap_or(1);
+        html! {
+            <div class="column is-hidden-touch">
+                <aside class="menu" style="position:sticky;top:1rem;">
+                    <p class="menu-label">{self.language.text("reader-toc")}</p>
+                    <ul class="menu-list">
+                    { for article.toc.iter().map(|heading| {
+                        let id = heading.id.clone();
+                        let indent = format!("padding-left:{}em;", f32::from(heading.level - top) + 0.75);
+                        html! {
+                            <li>
+                                <a href="javascript:void(0)" style=indent
+                                    onclick=self.link.callback(move |_| ReaderMessage::Jump(id.clone()))>
+                                    {&heading.text}
+                                </a>
+                            </li>
+                        }
+                    }) }
+                    </ul>
+                </aside>
+            </div>
+        }
+    }
+}
+
+#[test]
+fn test_is_web_url() {
+    assert!(is_web_url("https://example.com/blogs/hello/"));
+    assert!(is_web_url("http://example.com/a.md"));
+    assert!(!is_web_url("javascript:alert(1)"));
+    assert!(!is_web_url("/blogs/hello/"));
+    assert!(!is_web_url(""));
+}
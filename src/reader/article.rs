@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+
+use ammonia::UrlRelative;
+use pulldown_cmark::{html::push_html, Options, Parser};
+use url::Url;
+
+use super::highlight::{escape, highlight};
+use crate::blog_tile::request::Document;
+use crate::util::{decode_entities, is_cjk, strip_tags};
+
+/// Words per minute for text with spaces between words.
+const WORDS_PER_MINUTE: usize = 220;
+/// Characters per minute for Chinese and Japanese text.
+const CJK_PER_MINUTE: usize = 400;
+/// Prefix of heading ids, so they cannot clash with the page's own.
+const ID_PREFIX: &str = "read-";
+
+/// A heading in the table of contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: u8,
+    pub id: String,
+    pub text: String,
+}
+
+/// A post ready to be shown: sanitized HTML with highlighted code and
+/// anchored headings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Article {
+    /// The first `<h1>`, front matter `title` or `<title>`. A leading `<h1>`
+    /// is taken out of `html` so the title is not shown twice.
+    pub title: Option<String>,
+    pub html: String,
+    pub toc: Vec<Heading>,
+    pub minutes: usize,
+}
+
+/// Renders a fetched post. Markdown is converted to HTML; for HTML pages
+/// only the `<article>`, `<main>` or `<body>` is kept. Either way the result
+/// is sanitized and relative links point back at the post's site.
+pub fn render(document: &Document) -> Article {
+    let (mut title, html) = if is_markdown(document) {
+        let (title, markdown) = split_front_matter(&document.body);
+        (title, markdown_to_html(markdown))
+    } else {
+        (element_content(&document.body, "title").map(text_of), main_content(&document.body).to_string())
+    };
+    let html = sanitize(&html, &document.url);
+    let (heading, html, toc) = anchor_and_highlight(&html);
+    if heading.is_some() {
+        title = heading;
+    }
+    Article {
+        title: title.filter(|title| !title.is_empty()),
+        minutes: reading_minutes(&text_of(&html)),
+        html,
+        toc,
+    }
+}
+
+fn is_markdown(document: &Document) -> bool {
+    let mime = document
+        .content_type
+        .as_deref()
+        .and_then(|content_type| content_type.split(';').next())
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if mime.contains("markdown") {
+        return true;
+    }
+    if mime.contains("html") {
+        return false;
+    }
+    let path = Url::parse(&document.url).map(|url| url.path().to_lowercase()).unwrap_or_default();
+    path.ends_with(".md") || path.ends_with(".markdown") || !document.body.trim_start().starts_with('<')
+}
+
+/// Splits off a `---` delimited YAML header, returning its `title`.
+fn split_front_matter(markdown: &str) -> (Option<String>, &str) {
+    let header = match markdown.strip_prefix("---\n").or_else(|| markdown.strip_prefix("---\r\n")) {
+        Some(header) => header,
+        None => return (None, markdown),
+    };
+    let end = match header.find("\n---") {
+        Some(end) => end,
+        None => return (None, markdown),
+    };
+    let body = header[end + 4..].trim_start_matches('-');
+    let title = header[..end].lines().find_map(|line| {
+        let value = line.strip_prefix("title:")?.trim();
+        Some(value.trim_matches(|c| c == '"' || c == '\'').to_string())
+    });
+    (title, body)
+}
+
+fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+    push_html(&mut html, Parser::new_ext(markdown, options));
+    html
+}
+
+/// The inside of the first `<tag>` element, through its last closing tag.
+fn element_content<'a>(html: &'a str, tag: &str) -> Option<&'a str> {
+    // ASCII lowercasing keeps byte offsets, so they index `html` as well.
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", tag);
+    let start = lower.match_indices(&open).map(|(i, _)| i + open.len()).find(|&i| {
+        lower[i..].starts_with(|c: char| c == '>' || c.is_ascii_whitespace())
+    })?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = lower.rfind(&format!("</{}>", tag))?;
+    if end < start {
+        return None;
+    }
+    Some(&html[start..end])
+}
+
+/// The part of a web page that holds the post, without navigation bars,
+/// sidebars and the like where the site marks it up.
+fn main_content(html: &str) -> &str {
+    ["article", "main", "body"]
+        .iter()
+        .find_map(|tag| element_content(html, tag))
+        .unwrap_or(html)
+}
+
+fn sanitize(html: &str, base: &str) -> String {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tag_attributes("code", &["class"])
+        .link_rel(Some("noopener noreferrer"));
+    if let Ok(base) = Url::parse(base) {
+        builder.url_relative(UrlRelative::RewriteWithBase(base));
+    }
+    builder.clean(html).to_string()
+}
+
+/// Gives headings ids and collects them, and highlights `<pre><code>`
+/// blocks that name their language. Works on sanitizer output, which is
+/// well-formed and has no attributes on headings. Returns the text of a
+/// leading `<h1>`, which is dropped from the HTML.
+fn anchor_and_highlight(html: &str) -> (Option<String>, String, Vec<Heading>) {
+    let mut title = None;
+    let mut out = String::with_capacity(html.len() * 2);
+    let mut toc = Vec::new();
+    let mut ids = HashSet::new();
+    let mut leading = true;
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        leading &= rest[..start].trim().is_empty();
+        rest = &rest[start..];
+        if let Some((level, inner, len)) = heading_at(rest) {
+            let text = text_of(inner);
+            if level == 1 && leading && title.is_none() {
+                title = Some(text);
+            } else {
+                let id = unique_id(&text, &mut ids);
+                out.push_str(&format!("<h{} id=\"{}\">{}</h{}>", level, id, inner, level));
+                toc.push(Heading { level, id, text });
+            }
+            rest = &rest[len..];
+        } else if let Some((lang, code, len)) = code_block_at(rest) {
+            out.push_str(&format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>",
+                escape(lang),
+                highlight(&decode_entities(code), lang)
+            ));
+            rest = &rest[len..];
+        } else {
+            out.push('<');
+            rest = &rest[1..];
+        }
+        leading = false;
+    }
+    out.push_str(rest);
+    (title, out, toc)
+}
+
+/// A heading at the start of `html`: its level, inner HTML and length.
+fn heading_at(html: &str) -> Option<(u8, &str, usize)> {
+    let level = html.strip_prefix("<h")?.chars().next()?.to_digit(10)?;
+    if !(1..=6).contains(&level) || html.as_bytes().get(3) != Some(&b'>') {
+        return None;
+    }
+    let close = format!("</h{}>", level);
+    let end = html.find(&close)?;
+    Some((level as u8, &html[4..end], end + close.len()))
+}
+
+/// A code block with a language at the start of `html`: the language, the
+/// escaped code and the block's length.
+fn code_block_at(html: &str) -> Option<(&str, &str, usize)> {
+    let rest = html.strip_prefix("<pre><code class=\"")?;
+    let class_end = rest.find('"')?;
+    let lang = rest[..class_end]
+        .split_whitespace()
+        .find_map(|class| class.strip_prefix("language-"))?;
+    let code = rest[class_end..].strip_prefix("\">")?;
+    let end = code.find("</code></pre>")?;
+    Some((lang, &code[..end], html.len() - code.len() + end + "</code></pre>".len()))
+}
+
+fn unique_id(text: &str, ids: &mut HashSet<String>) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let base = format!("{}{}", ID_PREFIX, if slug.is_empty() { "section" } else { slug });
+    let mut id = base.clone();
+    let mut n = 1;
+    while !ids.insert(id.clone()) {
+        n += 1;
+        id = format!("{}-{}", base, n);
+    }
+    id
+}
+
+/// Text of an HTML fragment, without tags.
+fn text_of(html: &str) -> String {
+    decode_entities(strip_tags(html, "").trim())
+}
+
+/// Estimated minutes to read `text`, at least one.
+fn reading_minutes(text: &str) -> usize {
+    let cjk = text.chars().filter(|c| is_cjk(*c)).count();
+    let words = text
+        .split(|c: char| c.is_whitespace() || is_cjk(c))
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count();
+    let minutes = (words * CJK_PER_MINUTE + cjk * WORDS_PER_MINUTE) as f64 / (WORDS_PER_MINUTE * CJK_PER_MINUTE) as f64;
+    (minutes.ceil() as usize).max(1)
+}
+
+#[test]
+fn test_render_article() {
+    let markdown = Document {
+        url: "https://example.com/blogs/posts/hello.md".to_string(),
+        content_type: Some("text/plain; charset=utf-8".to_string()),
+        body: "# Hello\n\nSee ![cat](cat.png) and [home](/).\n\n## Setup\n\n```rust\nlet x = 1;\n```\n\n## Setup\n\n<script>alert(1)</script>\n"
+            .to_string(),
+    };
+    let article = render(&markdown);
+    assert_eq!(article.title.as_deref(), Some("Hello"));
+    assert!(!article.html.contains("<h1"));
+    assert!(!article.html.contains("<script"));
+    assert!(article.html.contains("src=\"https://example.com/blogs/posts/cat.png\""));
+    assert!(article.html.contains("href=\"https://example.com/\""));
+    assert!(article.html.contains("<span class=\"hl-keyword\">let</span> x = <span class=\"hl-number\">1</span>;"));
+    assert_eq!(
+        article.toc,
+        vec![
+            Heading { level: 2, id: "read-setup".to_string(), text: "Setup".to_string() },
+            Heading { level: 2, id: "read-setup-2".to_string(), text: "Setup".to_string() },
+        ]
+    );
+    assert!(article.html.contains("<h2 id=\"read-setup-2\">Setup</h2>"));
+    assert_eq!(article.minutes, 1);
+
+    let page = Document {
+        url: "https://example.com/blogs/hello/".to_string(),
+        content_type: Some("text/html".to_string()),
+        body: "<html><head><title>Hello &amp; bye</title></head><body><nav>Menu</nav>\
+               <article class=\"post\"><p onclick=\"x()\">Text</p><h2>Q &amp; A</h2></article></body></html>"
+            .to_string(),
+    };
+    let article = render(&page);
+    assert_eq!(article.title.as_deref(), Some("Hello & bye"));
+    assert_eq!(article.html, "<p>Text</p><h2 id=\"read-q-a\">Q &amp; A</h2>");
+
+    let front_matter = "---\ntitle: \"Notes\"\ndate: 2021-01-05\n---\nBody";
+    assert_eq!(split_front_matter(front_matter), (Some("Notes".to_string()), "\nBody"));
+    assert_eq!(reading_minutes(&"word ".repeat(440)), 2);
+    assert_eq!(reading_minutes(&"字".repeat(800)), 2);
+    assert_eq!(reading_minutes(""), 1);
+}
@@ -0,0 +1,220 @@
+//! A small lexical highlighter for code blocks in articles. It only knows
+//! keywords, strings, numbers and comments, which covers what posts show
+//! without shipping a grammar engine to the browser.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+impl Token {
+    /// CSS class, styled in `index.html`.
+    fn class(self) -> &'static str {
+        match self {
+            Token::Keyword => "hl-keyword",
+            Token::String => "hl-string",
+            Token::Number => "hl-number",
+            Token::Comment => "hl-comment",
+        }
+    }
+}
+
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+}
+
+const C_LIKE_COMMENTS: (&[&str], Option<(&str, &str)>) = (&["//"], Some(("/*", "*/")));
+
+fn syntax(lang: &str) -> Option<Syntax> {
+    let (keywords, (line_comments, block_comment), quotes): (&[&str], _, &[char]) =
+        match lang.to_lowercase().as_str() {
+            "rust" | "rs" => (
+                &[
+                    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+                    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+                    "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+                    "true", "type", "unsafe", "use", "where", "while",
+                ],
+                C_LIKE_COMMENTS,
+                &['"'],
+            ),
+            "js" | "javascript" | "ts" | "typescript" | "jsx" | "tsx" => (
+                &[
+                    "async", "await", "break", "case", "catch", "class", "const", "continue", "default",
+                    "delete", "else", "export", "extends", "false", "finally", "for", "from", "function",
+                    "if", "import", "in", "instanceof", "interface", "let", "new", "null", "of", "return",
+                    "switch", "this", "throw", "true", "try", "type", "typeof", "undefined", "var", "void",
+                    "while", "yield",
+                ],
+                C_LIKE_COMMENTS,
+                &['"', '\'', '`'],
+            ),
+            "go" | "golang" => (
+                &[
+                    "break", "case", "chan", "const", "continue", "default", "defer", "else", "false", "for",
+                    "func", "go", "if", "import", "interface", "map", "nil", "package", "range", "return",
+                    "select", "struct", "switch", "true", "type", "var",
+                ],
+                C_LIKE_COMMENTS,
+                &['"', '\'', '`'],
+            ),
+            "c" | "h" | "cpp" | "c++" | "cc" | "hpp" | "java" | "kotlin" | "kt" | "cs" | "csharp" => (
+                &[
+                    "auto", "bool", "break", "case", "catch", "char", "class", "const", "continue",
+                    "default", "delete", "do", "double", "else", "enum", "extends", "false", "final",
+                    "float", "for", "fun", "if", "implements", "import", "int", "long", "namespace", "new",
+                    "null", "nullptr", "package", "private", "protected", "public", "return", "short",
+                    "static", "struct", "switch", "template", "this", "throw", "true", "try", "typedef",
+                    "unsigned", "using", "val", "var", "virtual", "void", "while",
+                ],
+                C_LIKE_COMMENTS,
+                &['"', '\''],
+            ),
+            "python" | "py" => (
+                &[
+                    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+                    "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+                    "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+                    "try", "while", "with", "yield",
+                ],
+                (&["#"], None),
+                &['"', '\''],
+            ),
+            "sh" | "bash" | "shell" | "zsh" | "console" => (
+                &[
+                    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+                    "in", "local", "return", "then", "while",
+                ],
+                (&["#"], None),
+                &['"', '\''],
+            ),
+            "toml" | "yaml" | "yml" | "ini" => (&["true", "false"], (&["#"], None), &['"', '\'']),
+            "json" => (&["true", "false", "null"], (&[], None), &['"']),
+            "sql" => (
+                &[
+                    "and", "as", "by", "create", "delete", "from", "group", "insert", "into", "join", "not",
+                    "null", "on", "or", "order", "select", "set", "table", "update", "values", "where",
+                    "AND", "AS", "BY", "CREATE", "DELETE", "FROM", "GROUP", "INSERT", "INTO", "JOIN", "NOT",
+                    "NULL", "ON", "OR", "ORDER", "SELECT", "SET", "TABLE", "UPDATE", "VALUES", "WHERE",
+                ],
+                (&["--"], Some(("/*", "*/"))),
+                &['"', '\''],
+            ),
+            _ => return None,
+        };
+    Some(Syntax {
+        keywords,
+        line_comments,
+        block_comment,
+        quotes,
+    })
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders `code` as escaped HTML with `hl-*` spans. Languages it does not
+/// know come out escaped but plain.
+pub fn highlight(code: &str, lang: &str) -> String {
+    let syntax = match syntax(lang) {
+        Some(syntax) => syntax,
+        None => return escape(code),
+    };
+    let mut html = String::with_capacity(code.len() * 2);
+    let mut push = |token: Option<Token>, text: &str| match token {
+        Some(token) => html.push_str(&format!("<span class=\"{}\">{}</span>", token.class(), escape(text))),
+        None => html.push_str(&escape(text)),
+    };
+    let mut rest = code;
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = if let Some(len) = comment_len(&syntax, rest) {
+            (Some(Token::Comment), len)
+        } else if syntax.quotes.contains(&c) {
+            (Some(Token::String), string_len(rest, c))
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                .unwrap_or(rest.len());
+            (Some(Token::Number), len)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let keyword = syntax.keywords.contains(&&rest[..len]);
+            (if keyword { Some(Token::Keyword) } else { None }, len)
+        } else {
+            (None, c.len_utf8())
+        };
+        push(token, &rest[..len]);
+        rest = &rest[len..];
+    }
+    html
+}
+
+fn comment_len(syntax: &Syntax, rest: &str) -> Option<usize> {
+    if let Some((start, end)) = syntax.block_comment {
+        if let Some(body) = rest.strip_prefix(start) {
+            let len = body.find(end).map(|i| i + end.len()).unwrap_or(body.len());
+            return Some(start.len() + len);
+        }
+    }
+    syntax
+        .line_comments
+        .iter()
+        .find(|prefix| rest.starts_with(**prefix))
+        .map(|_| rest.find('\n').unwrap_or(rest.len()))
+}
+
+/// Length of the string literal at the start of `rest`, up to and including
+/// the closing `quote`, skipping escaped characters. Unterminated strings
+/// end at the line break.
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return i,
+            c if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+#[test]
+fn test_highlight() {
+    assert_eq!(
+        highlight("fn main() { let s = \"a<b\"; } // done", "rust"),
+        "<span class=\"hl-keyword\">fn</span> main() { <span class=\"hl-keyword\">let</span> s = \
+         <span class=\"hl-string\">&quot;a&lt;b&quot;</span>; } <span class=\"hl-comment\">// done</span>"
+    );
+    assert_eq!(
+        highlight("x = 'it\\'s' # 42\ny = 42", "python"),
+        "x = <span class=\"hl-string\">'it\\'s'</span> <span class=\"hl-comment\"># 42</span>\n\
+         y = <span class=\"hl-number\">42</span>"
+    );
+    assert_eq!(highlight("/* a\nb */1", "js"), "<span class=\"hl-comment\">/* a\nb */</span><span class=\"hl-number\">1</span>");
+    // Lifetimes are not strings in Rust.
+    assert_eq!(highlight("&'a str", "rust"), "&amp;'a str");
+    assert_eq!(highlight("<b>fn</b>", "unknown"), "&lt;b&gt;fn&lt;/b&gt;");
+    assert_eq!(highlight("\"open", "json"), "<span class=\"hl-string\">&quot;open</span>");
+}
@@ -0,0 +1,158 @@
+use yew::{web_sys, Callback};
+
+use crate::util::WindowListener;
+
+/// Pages of the site. Routes live in the location hash (`#/blogs`) so deep
+/// links keep working on a static host without server-side rewrites.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AppRoute {
+    Home,
+    Blogs,
+    Projects,
+    Learning,
+    About,
+    /// A post shown in the in-app reader, by its address.
+    Reader(String),
+    NotFound(String),
+}
+
+impl AppRoute {
+    pub fn from_hash(hash: &str) -> Self {
+        let hash = hash.trim_start_matches('#');
+        let path = hash.split('?').next().unwrap_or_default();
+        match path.trim_matches('/') {
+            "" => AppRoute::Home,
+            "blogs" => AppRoute::Blogs,
+            "projects" => AppRoute::Projects,
+            "learning" => AppRoute::Learning,
+            "about" => AppRoute::About,
+            "read" => match hash_query_param(hash, "url") {
+                Some(url) => AppRoute::Reader(url),
+                None => AppRoute::NotFound(path.to_string()),
+            },
+            _ => AppRoute::NotFound(path.to_string()),
+        }
+    }
+
+    pub fn to_hash(&self) -> String {
+        match self {
+            AppRoute::Home => "#/".to_string(),
+            AppRoute::Blogs => "#/blogs".to_string(),
+            AppRoute::Projects => "#/projects".to_string(),
+            AppRoute::Learning => "#/learning".to_string(),
+            AppRoute::About => "#/about".to_string(),
+            AppRoute::Reader(url) => with_query_param("#/read", "url", Some(url)),
+            AppRoute::NotFound(path) => format!("#{}", path),
+        }
+    }
+
+    pub fn current() -> Self {
+        Self::from_hash(&current_hash())
+    }
+}
+
+pub fn current_hash() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().hash().ok())
+        .unwrap_or_default()
+}
+
+/// Emits on every `hashchange` of the window, which covers navbar links,
+/// typed URLs and back/forward. The listener is removed on drop.
+pub struct RouteListener {
+    _listener: WindowListener,
+}
+
+impl RouteListener {
+    pub fn new(callback: Callback<AppRoute>) -> Self {
+        Self {
+            _listener: WindowListener::new(
+                "hashchange",
+                Callback::from(move |_| callback.emit(AppRoute::current())),
+            ),
+        }
+    }
+}
+
+/// Query parameters carried after the route in the hash, e.g. `page` in
+/// `#/blogs?page=2`.
+pub fn query_param(key: &str) -> Option<String> {
+    hash_query_param(&current_hash(), key)
+}
+
+/// Sets or, with `None`, removes query parameters of the current route.
+/// This adds a history entry, so back/forward walk through the states.
+pub fn set_query_params(params: &[(&str, Option<String>)]) {
+    let current = current_hash();
+    let hash = params.iter().fold(current.clone(), |hash, (key, value)| {
+        with_query_param(&hash, key, value.as_deref())
+    });
+    if hash == current {
+        return;
+    }
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().set_hash(&hash);
+    }
+}
+
+fn hash_query_param(hash: &str, key: &str) -> Option<String> {
+    let (_, query) = hash.split_once('?')?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.into_owned())
+}
+
+fn with_query_param(hash: &str, key: &str, value: Option<&str>) -> String {
+    let mut parts = hash.trim_start_matches('#').splitn(2, '?');
+    let path = parts.next().unwrap_or_default();
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (k, v) in url::form_urlencoded::parse(parts.next().unwrap_or_default().as_bytes()) {
+        if k != key {
+            query.append_pair(&k, &v);
+        }
+    }
+    if let Some(value) = value {
+        query.append_pair(key, value);
+    }
+    let query = query.finish();
+    let path = if path.is_empty() { "/" } else { path };
+    if query.is_empty() {
+        format!("#{}", path)
+    } else {
+        format!("#{}?{}", path, query)
+    }
+}
+
+#[test]
+fn test_route_from_hash() {
+    assert_eq!(AppRoute::from_hash(""), AppRoute::Home);
+    assert_eq!(AppRoute::from_hash("#/"), AppRoute::Home);
+    assert_eq!(AppRoute::from_hash("#/blogs"), AppRoute::Blogs);
+    assert_eq!(AppRoute::from_hash("#/projects/"), AppRoute::Projects);
+    assert_eq!(AppRoute::from_hash("#/learning?page=2"), AppRoute::Learning);
+    assert_eq!(AppRoute::from_hash("#/about"), AppRoute::About);
+    assert_eq!(
+        AppRoute::from_hash("#/nowhere"),
+        AppRoute::NotFound("/nowhere".to_string())
+    );
+    assert_eq!(
+        AppRoute::from_hash("#/read?url=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1"),
+        AppRoute::Reader("https://example.com/a?b=1".to_string())
+    );
+    assert_eq!(AppRoute::from_hash("#/read"), AppRoute::NotFound("/read".to_string()));
+    let reader = AppRoute::Reader("https://example.com/posts/a b/".to_string());
+    for route in [AppRoute::Home, AppRoute::Blogs, AppRoute::Projects, AppRoute::Learning, AppRoute::About, reader].iter() {
+        assert_eq!(&AppRoute::from_hash(&route.to_hash()), route);
+    }
+}
+
+#[test]
+fn test_hash_query() {
+    assert_eq!(hash_query_param("#/blogs?page=2&tag=Rust", "page"), Some("2".to_string()));
+    assert_eq!(hash_query_param("#/blogs?tag=a%20b", "tag"), Some("a b".to_string()));
+    assert_eq!(hash_query_param("#/blogs", "page"), None);
+    assert_eq!(with_query_param("#/blogs", "page", Some("2")), "#/blogs?page=2");
+    assert_eq!(with_query_param("#/blogs?page=2&tag=Rust", "page", Some("3")), "#/blogs?tag=Rust&page=3");
+    assert_eq!(with_query_param("#/blogs?page=2", "page", None), "#/blogs");
+    assert_eq!(with_query_param("", "page", Some("2")), "#/?page=2");
+}
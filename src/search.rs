@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use yew::{prelude::*, Properties};
+
+use crate::blog_tile::blog_card::{BlogCard, CardLayout};
+use crate::blog_tile::store::{FeedState, FeedStore, Request};
+use crate::blog_tile::{self, Blog, Blogs, FetchError};
+use crate::lang::Language;
+use crate::util::is_cjk;
+
+const TITLE_WEIGHT: u32 = 3;
+const TAG_WEIGHT: u32 = 2;
+const SUMMARY_WEIGHT: u32 = 1;
+const MAX_RESULTS: usize = 8;
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+enum Run {
+    Word(String),
+    Cjk(Vec<char>),
+}
+
+/// Splits text into lowercased alphanumeric words and runs of CJK
+/// characters, dropping everything else.
+fn runs(text: &str) -> Vec<Run> {
+    let mut ret = vec![];
+    for c in text.chars() {
+        if is_cjk(c) {
+            match ret.last_mut() {
+                Some(Run::Cjk(chars)) => chars.push(c),
+                _ => ret.push(Run::Cjk(vec![c])),
+            }
+        } else if c.is_alphanumeric() {
+            match ret.last_mut() {
+                Some(Run::Word(word)) => word.push(lowercase(c)),
+                _ => ret.push(Run::Word(lowercase(c).to_string())),
+            }
+        } else {
+            ret.push(Run::Word(String::new()));
+        }
+    }
+    ret.retain(|run| !matches!(run, Run::Word(word) if word.is_empty()));
+    ret
+}
+
+/// Index terms. Chinese and Japanese have no spaces between words, so CJK
+/// runs are indexed as every single character plus every adjacent pair.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut ret = vec![];
+    for run in runs(text) {
+        match run {
+            Run::Word(word) => ret.push(word),
+            Run::Cjk(chars) => {
+                ret.extend(chars.iter().map(|c| c.to_string()));
+                ret.extend(chars.windows(2).map(|pair| pair.iter().collect()));
+            }
+        }
+    }
+    ret
+}
+
+/// Query terms, all of which must match: words as typed, CJK runs as their
+/// adjacent pairs (or the single character of a one-character run).
+/// The flag marks terms that may match as a prefix.
+fn query_terms(query: &str) -> Vec<(String, bool)> {
+    let mut ret = vec![];
+    for run in runs(query) {
+        match run {
+            Run::Word(word) => ret.push((word, false)),
+            Run::Cjk(chars) if chars.len() == 1 => ret.push((chars[0].to_string(), false)),
+            Run::Cjk(chars) => ret.extend(chars.windows(2).map(|pair| (pair.iter().collect(), false))),
+        }
+    }
+    // The word being typed is usually incomplete.
+    if let Some((term, prefix)) = ret.last_mut() {
+        *prefix = !term.chars().any(is_cjk);
+    }
+    ret
+}
+
+/// In-memory inverted index over entry titles, tags and summaries.
+#[derive(Default)]
+pub struct SearchIndex {
+    docs: Vec<Blog>,
+    terms: HashMap<String, Vec<(usize, u32)>>,
+}
+
+impl SearchIndex {
+    pub fn new(docs: Vec<Blog>) -> Self {
+        let mut terms: HashMap<String, Vec<(usize, u32)>> = HashMap::new();
+        for (doc, blog) in docs.iter().enumerate() {
+            let fields = std::iter::once((blog.title.as_str(), TITLE_WEIGHT))
+                .chain(blog.tags.iter().map(|tag| (tag.as_str(), TAG_WEIGHT)))
+                .chain(std::iter::once((blog.summary.as_str(), SUMMARY_WEIGHT)));
+            for (text, weight) in fields {
+                for term in tokenize(text) {
+                    let postings = terms.entry(term).or_default();
+                    match postings.last_mut() {
+                        Some((last, score)) if *last == doc => *score += weight,
+                        _ => postings.push((doc, weight)),
+                    }
+                }
+            }
+        }
+        Self { docs, terms }
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Entries matching every query term, best first. Ties go to the newer
+    /// entry.
+    pub fn search(&self, query: &str) -> Vec<&Blog> {
+        let mut scores: Option<HashMap<usize, u32>> = None;
+        for (term, prefix) in query_terms(query) {
+            let mut hits: HashMap<usize, u32> = HashMap::new();
+            let matching = self
+                .terms
+                .iter()
+                .filter(|(t, _)| if prefix { t.starts_with(&term) } else { **t == term });
+            for (t, postings) in matching {
+                // Whole-word matches rank above prefix matches.
+                let boost = if *t == term { 2 } else { 1 };
+                for (doc, weight) in postings {
+                    *hits.entry(*doc).or_default() += weight * boost;
+                }
+            }
+            scores = Some(match scores {
+                None => hits,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(doc, score)| hits.get(&doc).map(|hit| (doc, score + hit)))
+                    .collect(),
+            });
+        }
+        let mut ret = scores.unwrap_or_default().into_iter().collect::<Vec<_>>();
+        ret.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .cmp(a_score)
+                .then(self.docs[*b].last_update.cmp(&self.docs[*a].last_update))
+        });
+        ret.into_iter().map(|(doc, _)| &self.docs[doc]).collect()
+    }
+}
+
+/// Splits `text` into segments, flagging those that match a query term.
+pub fn highlight(text: &str, query: &str) -> Vec<(String, bool)> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let lower = chars.iter().copied().map(lowercase).collect::<Vec<_>>();
+    let mut marked = vec![false; chars.len()];
+    for (term, _) in query_terms(query) {
+        let term = term.chars().collect::<Vec<_>>();
+        if term.is_empty() || term.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=lower.len() - term.len() {
+            if lower[start..start + term.len()] == term[..] {
+                marked[start..start + term.len()].iter_mut().for_each(|m| *m = true);
+            }
+        }
+    }
+    let mut ret: Vec<(String, bool)> = vec![];
+    for (c, mark) in chars.into_iter().zip(marked) {
+        match ret.last_mut() {
+            Some((segment, m)) if *m == mark => segment.push(c),
+            _ => ret.push((c.to_string(), mark)),
+        }
+    }
+    ret
+}
+
+pub fn render_highlight(text: &str, query: &str) -> Html {
+    highlight(text, query)
+        .into_iter()
+        .map(|(segment, mark)| {
+            if mark {
+                html! { <mark>{segment}</mark> }
+            } else {
+                html! { {segment} }
+            }
+        })
+        .collect()
+}
+
+/// Navbar search over every feed configured for any language. Feeds are
+/// only fetched once the box is first focused.
+pub struct SearchBox {
+    link: ComponentLink<Self>,
+    language: Language,
+    query: String,
+    index: SearchIndex,
+    /// One slot per entry of `feed_urls()`; `None` until it settles.
+    results: Vec<Option<FeedResult>>,
+    /// Connected on first focus.
+    store: Option<Box<dyn Bridge<FeedStore>>>,
+    props: SearchProperty,
+}
+
+#[derive(Properties, Clone)]
+pub struct SearchProperty {
+    #[prop_or("zh")]
+    pub lang: &'static str,
+}
+
+pub enum SearchMessage {
+    Focus,
+    Input(String),
+    Feed(FeedState),
+    Clear,
+}
+
+type FeedResult = Result<Rc<Blogs>, FetchError>;
+
+/// What the dropdown says when the query matches nothing.
+#[derive(Debug, PartialEq)]
+enum NoHits<'a> {
+    /// Nothing is indexed yet and some feeds have not answered.
+    Loading,
+    /// Every feed failed.
+    Failed(Vec<&'a FetchError>),
+    NoResults,
+}
+
+fn no_hits(results: &[Option<FeedResult>], indexed: usize) -> NoHits<'_> {
+    if indexed > 0 {
+        return NoHits::NoResults;
+    }
+    if results.iter().any(Option::is_none) {
+        return NoHits::Loading;
+    }
+    let errors = results.iter().flatten().filter_map(|result| result.as_ref().err()).collect::<Vec<_>>();
+    if !errors.is_empty() && errors.len() == results.len() {
+        NoHits::Failed(errors)
+    } else {
+        NoHits::NoResults
+    }
+}
+
+fn feed_urls() -> Vec<&'static str> {
+    let mut urls: Vec<&str> = vec![];
+    for source in crate::config::site().blogs.values().flatten() {
+        if !urls.contains(&source.url.as_str()) {
+            urls.push(&source.url);
+        }
+    }
+    urls
+}
+
+impl Component for SearchBox {
+    type Message = SearchMessage;
+
+    type Properties = SearchProperty;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            language: Language::from_lang(props.lang),
+            query: String::new(),
+            index: SearchIndex::default(),
+            results: Vec::new(),
+            store: None,
+            props,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            SearchMessage::Focus => {
+                if self.store.is_some() {
+                    return false;
+                }
+                let urls = feed_urls();
+                self.results = urls.iter().map(|_| None).collect();
+                let mut store = FeedStore::bridge(self.link.callback(SearchMessage::Feed));
+                store.send(Request::Watch(urls.iter().map(|url| url.to_string()).collect()));
+                self.store = Some(store);
+                false
+            }
+            SearchMessage::Input(query) => {
+                self.query = query;
+                true
+            }
+            SearchMessage::Feed(state) => {
+                // A failed revalidation keeps the entries fetched before.
+                let FeedState { url, blogs, error, .. } = state;
+                let settled = match (blogs, error) {
+                    (Some(blogs), _) => Ok(blogs),
+                    (None, Some(err)) => Err(err),
+                    (None, None) => return false,
+                };
+                let slot = feed_urls().iter().position(|feed| *feed == url);
+                match slot.and_then(|slot| self.results.get_mut(slot)) {
+                    Some(result) => *result = Some(settled),
+                    None => return false,
+                }
+                let done = self
+                    .results
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(slot, result)| Some((slot, &**result.as_ref()?.as_ref().ok()?)))
+                    .collect::<Vec<_>>();
+                self.index = SearchIndex::new(blog_tile::merge_blogs(&done).blogs);
+                !self.query.is_empty()
+            }
+            SearchMessage::Clear => {
+                self.query.clear();
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props.lang == props.lang {
+            false
+        } else {
+            self.language = Language::from_lang(props.lang);
+            self.props = props;
+            true
+        }
+    }
+
+    fn view(&self) -> Html {
+        let active = if self.query.trim().is_empty() { "" } else { "is-active" };
+        html! {
+            <div class=("dropdown", "is-right", active)>
+                <div class="dropdown-trigger">
+                    <input class="input is-small" type="search"
+                        placeholder=self.language.text("search-placeholder")
+                        value=&self.query
+                        onfocus=self.link.callback(|_| SearchMessage::Focus)
+                        oninput=self.link.callback(|e: InputData| SearchMessage::Input(e.value)) />
+                </div>
+                <div class="dropdown-menu" style="min-width:22rem;">
+                    <div class="dropdown-content" style="max-height:70vh; overflow-y:auto;">
+                        { self.view_results() }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl SearchBox {
+    fn view_results(&self) -> Html {
+        let hits = self.index.search(&self.query);
+        if hits.is_empty() {
+            let text = match no_hits(&self.results, self.index.len()) {
+                NoHits::Loading => self.language.text("search-loading"),
+                NoHits::Failed(errors) => {
+                    let reasons = errors.iter().map(|err| err.describe(&self.language)).collect::<Vec<_>>();
+                    let mut args = fluent_bundle::FluentArgs::new();
+                    args.set("reason", reasons.join("; "));
+                    self.language.format("search-failed", Some(&args))
+                }
+                NoHits::NoResults => self.language.text("search-no-results"),
+            };
+            return html! { <div class="dropdown-item"><p class="has-text-grey">{text}</p></div> };
+        }
+        hits.into_iter()
+            .take(MAX_RESULTS)
+            .map(|blog| {
+                html! {
+                    <BlogCard lang={self.language.lang} blog=blog.clone() layout=CardLayout::Compact show_date=false
+                        highlight=self.query.clone() classes="dropdown-item"
+                        onclick=self.link.callback(|_| SearchMessage::Clear) />
+                }
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(tokenize("Async PyO3, 2021!"), vec!["async", "pyo3", "2021"]);
+    assert_eq!(tokenize("使用PyO3异步"), vec!["使", "用", "使用", "pyo3", "异", "步", "异步"]);
+    assert_eq!(
+        query_terms("跨越编程 asy"),
+        vec![
+            ("跨越".to_string(), false),
+            ("越编".to_string(), false),
+            ("编程".to_string(), false),
+            ("asy".to_string(), true),
+        ]
+    );
+}
+
+#[test]
+fn test_search_index() {
+    let blog = |title: &str, summary: &str, tags: &[&str], millis| Blog {
+        summary: summary.to_string(),
+        ..blog_tile::blog(title, tags, millis)
+    };
+    let index = SearchIndex::new(vec![
+        blog("使用PyO3跨越编程语言的异步", "Rust 与 Python", &["Rust"], 1),
+        blog("Rust async runtimes", "tokio and async-std", &[], 2),
+        blog("Dummy", "nothing about rust here", &[], 3),
+    ]);
+    let titles = |query| index.search(query).iter().map(|b| b.title.clone()).collect::<Vec<_>>();
+
+    assert_eq!(titles("rust"), vec!["Rust async runtimes", "使用PyO3跨越编程语言的异步", "Dummy"]);
+    assert_eq!(titles("rust asy"), vec!["Rust async runtimes"]);
+    assert_eq!(titles("编程语言"), vec!["使用PyO3跨越编程语言的异步"]);
+    assert_eq!(titles("异"), vec!["使用PyO3跨越编程语言的异步"]);
+    assert!(titles("编语").is_empty());
+    assert!(titles("").is_empty());
+}
+
+#[test]
+fn test_no_hits() {
+    let blogs = Rc::new(Blogs {
+        last_update: crate::util::epoch(),
+        blogs: vec![],
+        warnings: vec![],
+        meta: None,
+    });
+    let failed = || Some(Err(FetchError::Timeout));
+    assert_eq!(no_hits(&[None, Some(Ok(blogs.clone()))], 0), NoHits::Loading);
+    assert_eq!(no_hits(&[None, failed()], 3), NoHits::NoResults);
+    assert_eq!(no_hits(&[failed(), failed()], 0), NoHits::Failed(vec![&FetchError::Timeout; 2]));
+    // Feeds that answered without posts settle too.
+    assert_eq!(no_hits(&[failed(), Some(Ok(blogs))], 0), NoHits::NoResults);
+    assert_eq!(no_hits(&[], 0), NoHits::NoResults);
+}
+
+#[test]
+fn test_highlight() {
+    assert_eq!(
+        highlight("Rust async in RUST", "rust"),
+        vec![
+            ("Rust".to_string(), true),
+            (" async in ".to_string(), false),
+            ("RUST".to_string(), true),
+        ]
+    );
+    assert_eq!(
+        highlight("跨越编程语言", "编程语"),
+        vec![("跨越".to_string(), false), ("编程语".to_string(), true), ("言".to_string(), false)]
+    );
+}
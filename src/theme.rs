@@ -0,0 +1,174 @@
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::MediaQueryList;
+use yew::Callback;
+
+const STORAGE_KEY: &str = "io-index.theme";
+const DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+
+/// What the reader picked in the navbar.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThemeChoice {
+    Light,
+    Dark,
+    /// Whatever the system asks for through `prefers-color-scheme`.
+    #[default]
+    System,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 3] = [ThemeChoice::Light, ThemeChoice::Dark, ThemeChoice::System];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThemeChoice::Light => "light",
+            ThemeChoice::Dark => "dark",
+            ThemeChoice::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|choice| choice.as_str() == value)
+    }
+
+    /// Message id of the choice's label.
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeChoice::Light => "theme-light",
+            ThemeChoice::Dark => "theme-dark",
+            ThemeChoice::System => "theme-system",
+        }
+    }
+
+    pub fn resolve(self, system_dark: bool) -> Theme {
+        match self {
+            ThemeChoice::Light => Theme::Light,
+            ThemeChoice::Dark => Theme::Dark,
+            ThemeChoice::System if system_dark => Theme::Dark,
+            ThemeChoice::System => Theme::Light,
+        }
+    }
+}
+
+/// The theme in effect, with the Bulma classes each part of the page takes
+/// in it. Bulma has no dark mode of its own; what its helpers do not cover
+/// is styled under `html[data-theme="dark"]` in `index.html`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    pub fn navbar(self) -> &'static str {
+        match self {
+            Theme::Light => "is-light",
+            Theme::Dark => "is-dark",
+        }
+    }
+
+    pub fn hero(self) -> &'static str {
+        match self {
+            Theme::Light => "is-primary",
+            Theme::Dark => "is-dark",
+        }
+    }
+
+    /// Boxes holding the tiles of a page.
+    pub fn tile(self) -> &'static str {
+        match self {
+            Theme::Light => "",
+            Theme::Dark => "has-background-black-ter",
+        }
+    }
+
+    pub fn footer(self) -> &'static str {
+        match self {
+            Theme::Light => "",
+            Theme::Dark => "has-background-black-bis has-text-grey-light",
+        }
+    }
+}
+
+/// The choice saved by `save_choice`, or following the system.
+pub fn initial_choice() -> ThemeChoice {
+    yew::services::StorageService::new(yew::services::storage::Area::Local)
+        .ok()
+        .and_then(|storage| storage.restore::<Result<String, anyhow::Error>>(STORAGE_KEY).ok())
+        .and_then(|value| ThemeChoice::parse(&value))
+        .unwrap_or_default()
+}
+
+/// Remembers the choice for the next visit.
+pub fn save_choice(choice: ThemeChoice) {
+    if let Ok(mut storage) = yew::services::StorageService::new(yew::services::storage::Area::Local) {
+        storage.store(STORAGE_KEY, Ok(choice.as_str().to_string()));
+    }
+}
+
+/// Sets `<html data-theme>` for the styles in `index.html`.
+pub fn apply(theme: Theme) {
+    let root = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.document_element());
+    if let Some(root) = root {
+        let _ = root.set_attribute("data-theme", theme.as_str());
+    }
+}
+
+fn dark_query() -> Option<MediaQueryList> {
+    web_sys::window()?.match_media(DARK_QUERY).ok().flatten()
+}
+
+/// Whether the system currently prefers a dark theme.
+pub fn system_dark() -> bool {
+    dark_query().is_some_and(|query| query.matches())
+}
+
+/// Emits whether the system prefers a dark theme whenever that changes,
+/// e.g. when the OS switches at sunset. The listener is removed on drop.
+pub struct SystemThemeListener {
+    query: Option<MediaQueryList>,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl SystemThemeListener {
+    pub fn new(callback: Callback<bool>) -> Self {
+        let query = dark_query();
+        let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            callback.emit(system_dark());
+        }) as Box<dyn FnMut(web_sys::Event)>);
+        if let Some(query) = &query {
+            let _ = query.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref());
+        }
+        Self { query, closure }
+    }
+}
+
+impl Drop for SystemThemeListener {
+    fn drop(&mut self) {
+        if let Some(query) = &self.query {
+            let _ = query.remove_event_listener_with_callback("change", self.closure.as_ref().unchecked_ref());
+        }
+    }
+}
+
+#[test]
+fn test_theme_choice() {
+    for choice in ThemeChoice::ALL.iter() {
+        assert_eq!(ThemeChoice::parse(choice.as_str()), Some(*choice));
+    }
+    assert_eq!(ThemeChoice::parse("sepia"), None);
+    assert_eq!(ThemeChoice::System.resolve(true), Theme::Dark);
+    assert_eq!(ThemeChoice::System.resolve(false), Theme::Light);
+    assert_eq!(ThemeChoice::Light.resolve(true), Theme::Light);
+    assert_eq!(ThemeChoice::Dark.resolve(false), Theme::Dark);
+    assert_eq!(Theme::Light.hero(), "is-primary");
+    assert_eq!(Theme::Dark.navbar(), "is-dark");
+}
@@ -1,6 +1,169 @@
 use chrono::{DateTime, TimeZone, Utc};
+use wasm_bindgen::{prelude::*, JsCast};
+use yew::{web_sys, Callback};
 
 pub fn now() -> DateTime<Utc> {
     let timestamp:i64 = js_sys::Date::now().floor() as i64;
-    chrono::Utc.timestamp_millis(timestamp)
-}
\ No newline at end of file
+    from_millis(timestamp)
+}
+
+pub fn from_millis(timestamp: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(timestamp).single().unwrap_or_else(epoch)
+}
+
+pub fn epoch() -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(0).unwrap()
+}
+
+/// Forwards a DOM event of the window to a component callback for as long
+/// as it is alive.
+pub struct WindowListener {
+    event: &'static str,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl WindowListener {
+    pub fn new(event: &'static str, callback: Callback<()>) -> Self {
+        let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            callback.emit(());
+        }) as Box<dyn FnMut(web_sys::Event)>);
+        if let Some(window) = web_sys::window() {
+            let _ = window.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref());
+        }
+        Self { event, closure }
+    }
+}
+
+impl Drop for WindowListener {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            let _ = window.remove_event_listener_with_callback(
+                self.event,
+                self.closure.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
+
+/// Whether the viewport is within `margin` pixels of the end of the page.
+pub fn near_page_bottom(margin: f64) -> bool {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return false,
+    };
+    let viewport = window.inner_height().ok().and_then(|h| h.as_f64()).unwrap_or_default();
+    let scrolled = window.scroll_y().unwrap_or_default();
+    let height = window
+        .document()
+        .and_then(|d| d.body())
+        .map(|b| b.offset_height() as f64)
+        .unwrap_or_default();
+    viewport + scrolled + margin >= height
+}
+
+/// Parses a sitemap `<lastmod>`: any W3C datetime precision (`2021`,
+/// `2021-01`, `2021-01-05`, `2021-01-05T10:00+08:00`, with optional seconds
+/// and fraction) or integer epoch milliseconds as emitted by our own sitemap.
+pub fn parse_w3c_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if !value.is_empty() && value.len() != 4 && value.bytes().all(|b| b.is_ascii_digit()) {
+        return value.parse().ok().map(from_millis);
+    }
+    let (date, time) = match value.find('T') {
+        Some(index) => (&value[..index], Some(&value[index + 1..])),
+        None => (value, None),
+    };
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?;
+    if year.len() != 4 {
+        return None;
+    }
+    let date = chrono::NaiveDate::from_ymd_opt(
+        year.parse().ok()?,
+        parts.next().map_or(Some(1), |m| m.parse().ok())?,
+        parts.next().map_or(Some(1), |d| d.parse().ok())?,
+    )?;
+    let time = match time {
+        Some(time) => time,
+        None => return Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?)),
+    };
+    let (clock, offset) = match time.find(&['Z', '+', '-'][..]) {
+        Some(index) => (&time[..index], &time[index..]),
+        None => (time, "Z"),
+    };
+    let clock = if clock.len() == 5 { format!("{}:00", clock) } else { clock.to_string() };
+    let offset = if offset == "Z" { "+00:00" } else { offset };
+    DateTime::parse_from_rfc3339(&format!("{}T{}{}", date, clock, offset))
+        .ok()
+        .map(|datetime| datetime.with_timezone(&Utc))
+}
+
+/// Chinese, Japanese and Korean characters, which are read and searched
+/// one by one rather than as space-separated words.
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF // Hiragana, Katakana
+        | 0x3400..=0x4DBF // CJK Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF // Hangul Syllables
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0x20000..=0x2FA1F) // CJK Extension B and later
+}
+
+/// The text of an HTML fragment with every tag replaced by `between`.
+/// Entities are left alone, see `decode_entities`.
+pub(crate) fn strip_tags(html: &str, between: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push_str(between);
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Undoes the escaping of text in HTML: the entities that escapers emit,
+/// not the full named set.
+pub(crate) fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&amp;", "&")
+}
+
+#[test]
+fn test_parse_w3c_datetime() {
+    let utc = |y, m, d, h, min, s| Utc.with_ymd_and_hms(y, m, d, h, min, s).unwrap();
+    assert_eq!(parse_w3c_datetime("1609859438225"), Some(from_millis(1609859438225)));
+    assert_eq!(parse_w3c_datetime("2021"), Some(utc(2021, 1, 1, 0, 0, 0)));
+    assert_eq!(parse_w3c_datetime("2021-03"), Some(utc(2021, 3, 1, 0, 0, 0)));
+    assert_eq!(parse_w3c_datetime(" 2021-01-05 "), Some(utc(2021, 1, 5, 0, 0, 0)));
+    assert_eq!(parse_w3c_datetime("2021-01-05T10:00+08:00"), Some(utc(2021, 1, 5, 2, 0, 0)));
+    assert_eq!(parse_w3c_datetime("2021-01-05T10:00:30Z"), Some(utc(2021, 1, 5, 10, 0, 30)));
+    assert_eq!(
+        parse_w3c_datetime("2021-01-05T10:00:30.45-01:00"),
+        Some(utc(2021, 1, 5, 11, 0, 30) + chrono::Duration::milliseconds(450))
+    );
+    assert_eq!(parse_w3c_datetime("2021-01-05T10:00:30"), Some(utc(2021, 1, 5, 10, 0, 30)));
+    assert_eq!(parse_w3c_datetime("2021-13-01"), None);
+    assert_eq!(parse_w3c_datetime("yesterday"), None);
+    assert_eq!(parse_w3c_datetime(""), None);
+}
+
+#[test]
+fn test_html_text() {
+    assert!(is_cjk('异') && is_cjk('\u{20000}') && is_cjk('カ'));
+    assert!(!is_cjk('a') && !is_cjk('，'));
+    assert_eq!(strip_tags("<p>a<b>b</b></p>c", ""), "abc");
+    assert_eq!(strip_tags("<p>a</p><p>b</p>", " "), " a  b ");
+    assert_eq!(decode_entities("&amp;lt; &lt;b&gt; &quot;&#39;&nbsp;"), "&lt; <b> \"'\u{a0}");
+}